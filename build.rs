@@ -0,0 +1,95 @@
+//! Embeds the build metadata printed by `search --version`: the git
+//! commit, the build date and the enabled cargo features.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rustc-env=SEARCH_GIT_COMMIT={}", git_commit());
+    println!("cargo:rustc-env=SEARCH_BUILD_DATE={}", build_date());
+    println!("cargo:rustc-env=SEARCH_FEATURES={}", features().join(","));
+    println!("cargo:rustc-env=SEARCH_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=SEARCH_RUSTC_VERSION={}", rustc_version());
+
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    rerun_if_head_changed();
+}
+
+/// The short hash of the commit being built, or `unknown` outside of a
+/// git checkout.
+fn git_commit() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+/// The build date as `YYYY-MM-DD`, honouring `SOURCE_DATE_EPOCH` for
+/// reproducible builds.
+fn build_date() -> String {
+    let seconds = env::var("SOURCE_DATE_EPOCH").ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+    // Days to civil date, from Howard Hinnant's `civil_from_days`.
+    let z = (seconds / 86400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The cargo features enabled for this build, in lowercase.
+fn features() -> Vec<String> {
+    let mut features = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase().replace('_', "-")))
+        .collect::<Vec<_>>();
+
+    features.sort();
+    features
+}
+
+fn rustc_version() -> String {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+
+    Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Rebuild when the checked out commit changes.
+fn rerun_if_head_changed() {
+    let head = Path::new(".git/HEAD");
+    if !head.exists() {
+        return;
+    }
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+
+    if let Ok(contents) = fs::read_to_string(head) {
+        if let Some(reference) = contents.trim().strip_prefix("ref: ") {
+            println!("cargo:rerun-if-changed=.git/{}", reference);
+        }
+    }
+}
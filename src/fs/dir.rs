@@ -1,46 +1,33 @@
-use std::{path::PathBuf, io, slice::Iter as SliceIter};
+use std::{fs::ReadDir, path::PathBuf, io};
 use crate::fs::file::File;
 
+/// A directory being read. The entries are streamed from the OS as they
+/// are asked for instead of being collected first, so a directory costs
+/// the same memory whatever its number of entries.
 pub struct Dir {
-    contents: Vec<PathBuf>,
+    entries: ReadDir,
 
     pub path: PathBuf
 }
 
 impl Dir {
     pub fn read_dir(path: PathBuf) -> io::Result<Self> {
-        let contents = std::fs::read_dir(&path)?
-            .map(|f| f.map(|entry| entry.path()))
-            .collect::<Result<_, _>>()?;
+        let entries = std::fs::read_dir(&path)?;
 
-        Ok(Self { contents, path })
+        Ok(Self { entries, path })
     }
 
-    pub fn files<'dir>(&'dir self) -> Files<'dir> {
-        Files { 
-            inner: self.contents.iter(),
-            dir: self 
-        }
+    /// Read the next entry of the directory. An error is returned with the
+    /// path of the entry, or with the path of the directory when the entry
+    /// itself couldn't be read. A bad entry doesn't prevent the next ones
+    /// from being read.
+    pub fn next_file(&mut self) -> Option<Result<File<'_>, (PathBuf, io::Error)>> {
+        let entry = match self.entries.next()? {
+            Ok(entry) => entry,
+            Err(e) => return Some(Err((self.path.clone(), e))),
+        };
+
+        Some(File::from_entry(&entry, &*self)
+            .map_err(|e| (entry.path(), e)))
     }
 }
-
-pub struct Files<'dir> {
-    inner: SliceIter<'dir, PathBuf>,
-
-    dir: &'dir Dir
-}
-
-impl<'dir> Iterator for Files<'dir> {
-    type Item = Result<File<'dir>, (PathBuf, io::Error)>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(path) = self.inner.next() {
-            let file_name = File::filename(path);
-
-            return Some(File::from_args(path.clone(), self.dir, file_name)
-                .map_err(|e| (path.clone(), e)))
-        }
-
-        None
-    }
-}
\ No newline at end of file
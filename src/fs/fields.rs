@@ -1,3 +1,4 @@
+#[allow(dead_code)]
 #[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub enum Type {
     Directory,
@@ -10,6 +11,7 @@ pub enum Type {
     Special,
 }
 
+#[allow(dead_code)]
 impl Type {
     pub fn is_regular_file(self) -> bool {
         matches!(self, Self::File)
@@ -1,16 +1,28 @@
 use std::{path::{PathBuf, Path}, fs, io};
+use std::cell::RefCell;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
 
 use crate::fs::dir::Dir;
 
 #[derive(Clone)]
 pub struct File<'dir> {
-    pub name: String,
+    /// The name of the file, as the OS gives it. It may not be valid UTF-8.
+    pub name: OsString,
 
     pub ext: Option<String>,
 
     pub path: PathBuf,
 
-    pub metadata: fs::Metadata,
+    /// The type of the file, known without a `stat` when it comes from a
+    /// directory entry.
+    pub file_type: fs::FileType,
+
+    /// The metadata of the file, read the first time it's needed. Most
+    /// filters only need the name and the type of a file, so most files
+    /// are never `stat`ed.
+    metadata: RefCell<Option<fs::Metadata>>,
 
     pub parent_dir: Option<&'dir Dir>,
 
@@ -22,32 +34,61 @@ impl<'dir> File<'dir> {
     pub fn from_args<PD, FN>(path: PathBuf, parent_dir: PD, file_name: FN) -> io::Result<File<'dir>>
     where 
         PD: Into<Option<&'dir Dir>>,
-        FN: Into<Option<String>> 
+        FN: Into<Option<OsString>> 
     {
         let name = file_name.into().unwrap_or_else(|| File::filename(&path));
         let parent_dir = parent_dir.into();
         let ext = File::ext(&path);
         let metadata = fs::symlink_metadata(&path)?;
+        let file_type = metadata.file_type();
+        let metadata = RefCell::new(Some(metadata));
+        let is_dot_or_dot_dot = false;
+
+        Ok(File { name, ext, path, file_type, metadata, parent_dir, is_dot_or_dot_dot })
+    }
+
+    /// Create a file from an entry of its parent directory. The type of the
+    /// entry is given by the OS along with its name, so no `stat` is done.
+    pub fn from_entry(entry: &fs::DirEntry, parent_dir: &'dir Dir) -> io::Result<File<'dir>> {
+        let path = entry.path();
+        let name = entry.file_name();
+        let ext = File::ext(&path);
+        let file_type = entry.file_type()?;
+        let metadata = RefCell::new(None);
         let is_dot_or_dot_dot = false;
 
-        Ok(File { name, ext, path, metadata, parent_dir, is_dot_or_dot_dot })
+        Ok(File { name, ext, path, file_type, metadata, parent_dir: Some(parent_dir), is_dot_or_dot_dot })
+    }
+
+    /// The metadata of the file, not following symlinks. It's read from
+    /// the filesystem on the first call, and cached.
+    pub fn metadata(&self) -> io::Result<fs::Metadata> {
+        if let Some(metadata) = &*self.metadata.borrow() {
+            return Ok(metadata.clone());
+        }
+
+        let metadata = fs::symlink_metadata(&self.path)?;
+        *self.metadata.borrow_mut() = Some(metadata.clone());
+        Ok(metadata)
     }
 
-    pub fn filename(path: &Path) -> String {
+    pub fn filename(path: &Path) -> OsString {
         if let Some(back) = path.components().next_back() {
-            back.as_os_str().to_string_lossy().to_string()
+            back.as_os_str().to_os_string()
         } else {
-            path.display().to_string()
+            path.as_os_str().to_os_string()
         }
     }
 
+    /// The lowercased extension of the file name. Like `Path::extension`,
+    /// the leading dot of a hidden file such as `.bashrc` doesn't start an
+    /// extension, and a name ending with a dot has none. It's only used to
+    /// be compared with the extensions given by the user, so an extension
+    /// that isn't valid UTF-8 is converted lossily.
     pub fn ext(path: &Path) -> Option<String> {
-        let name = path
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())?;
-
-        name.rfind(".")
-            .map(|p| name[p+1..].to_ascii_lowercase())
+        path.extension()
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| String::from_utf8_lossy(ext.as_bytes()).to_ascii_lowercase())
     }
 
     pub fn to_dir(&self) -> io::Result<Dir> {
@@ -55,15 +96,88 @@ impl<'dir> File<'dir> {
     }
 
     pub fn is_directory(&self) -> bool {
-        self.metadata.is_dir()
+        self.file_type.is_dir()
+    }
+
+    /// Whether the file is a directory, or a symlink to one. The paths
+    /// given on the command line are walked when they point to a
+    /// directory, like `find -H` does.
+    pub fn points_to_directory(&self) -> bool {
+        self.is_directory() || (self.is_link() && fs::metadata(&self.path).map_or(false, |metadata| metadata.is_dir()))
     }
 
     pub fn is_file(&self) -> bool {
-        self.metadata.is_file()
+        self.file_type.is_file()
+    }
+
+    /// Whether the file is a regular file with any of its executable bits
+    /// set.
+    pub fn is_executable_file(&self) -> bool {
+        let bit = 0o111;
+        self.is_file() && self.metadata().map_or(false, |metadata| (metadata.permissions().mode() & bit) != 0)
     }
 
-    // Get size of a file in bytes
-    pub fn get_size(&self) -> u64 {
-        self.metadata.len()
+    pub fn is_link(&self) -> bool {
+        self.file_type.is_symlink()
     }
-}
\ No newline at end of file
+
+    /// Read the target of a symlink. The target path is kept as written in
+    /// the link, but it's resolved from the link's directory to check
+    /// whether it exists.
+    pub fn link_target(&self) -> FileTarget<'dir> {
+        let path = match fs::read_link(&self.path) {
+            Ok(path) => path,
+            Err(e) => return FileTarget::Err(e),
+        };
+
+        let absolute_path = match self.path.parent() {
+            Some(parent) => parent.join(&path),
+            None => path.clone(),
+        };
+
+        match fs::metadata(absolute_path) {
+            Ok(metadata) => {
+                let name = File::filename(&path);
+                let ext = File::ext(&path);
+
+                let file_type = metadata.file_type();
+                let metadata = RefCell::new(Some(metadata));
+
+                FileTarget::Ok(Box::new(File { name, ext, path, file_type, metadata, parent_dir: None, is_dot_or_dot_dot: false }))
+            },
+            Err(_) => FileTarget::Broken(path),
+        }
+    }
+
+    // Get size of a file in bytes, if its metadata can be read
+    pub fn get_size(&self) -> Option<u64> {
+        self.metadata().ok().map(|metadata| metadata.len())
+    }
+}
+
+/// The result of reading the target of a symlink.
+pub enum FileTarget<'dir> {
+    /// The symlink points to an existing file.
+    Ok(Box<File<'dir>>),
+
+    /// The symlink points to a file that doesn't exist.
+    Broken(PathBuf),
+
+    /// The symlink couldn't be read.
+    Err(io::Error),
+}
+
+impl<'dir> FileTarget<'dir> {
+    pub fn is_broken(&self) -> bool {
+        matches!(self, Self::Broken(_))
+    }
+
+    /// The target path, as written in the link, if it could be read.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Ok(file) => Some(&file.path),
+            Self::Broken(path) => Some(path),
+            Self::Err(_) => None,
+        }
+    }
+}
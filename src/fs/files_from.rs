@@ -0,0 +1,72 @@
+use std::ffi::OsStr;
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+/// Where a list of paths to search is read from, instead of walking the
+/// filesystem to find them.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FilesFrom {
+    /// The standard input, given as `-`.
+    Stdin,
+
+    /// A file.
+    File(PathBuf),
+}
+
+impl FilesFrom {
+    /// Read the paths of the list, one per line. The carriage return of a
+    /// list written with CRLF line endings is not part of the path, and
+    /// empty lines are skipped.
+    pub fn read(&self) -> io::Result<Vec<PathBuf>> {
+        let mut contents = Vec::new();
+
+        match self {
+            Self::Stdin => io::stdin().lock().read_to_end(&mut contents)?,
+            Self::File(path) => std::fs::File::open(path)?.read_to_end(&mut contents)?,
+        };
+
+        let paths = contents.split(|byte| *byte == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .filter(|line| !line.is_empty())
+            .map(|line| PathBuf::from(OsStr::from_bytes(line)))
+            .collect();
+
+        Ok(paths)
+    }
+
+    /// The name of the list, as printed in the error messages.
+    pub fn name(&self) -> PathBuf {
+        match self {
+            Self::Stdin => PathBuf::from("(standard input)"),
+            Self::File(path) => path.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod files_from_test {
+    use super::*;
+
+    use crate::fs::test::TempDir;
+
+    fn read(name: &str, contents: &str) -> Vec<PathBuf> {
+        let temp = TempDir::new(name);
+        FilesFrom::File(temp.file("list", contents, 0o644)).read().unwrap()
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(read("files-from-lines", "a\nb c\n\nd"), vec![PathBuf::from("a"), PathBuf::from("b c"), PathBuf::from("d")]);
+    }
+
+    #[test]
+    fn crlf_lines() {
+        assert_eq!(read("files-from-crlf", "a\r\nb\r\n\r\nc\r"), vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")]);
+    }
+
+    #[test]
+    fn missing_list() {
+        assert!(FilesFrom::File(PathBuf::from("/search-test-missing-list")).read().is_err());
+    }
+}
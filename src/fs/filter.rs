@@ -1,6 +1,14 @@
-use regex::Regex;
+use std::ffi::CString;
+use std::ops::Range;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use regex::bytes::Regex;
 
 use crate::fs::file::File;
+use crate::fs::users::UsersCache;
 
 /// A file filter. This is used to filter files based on their properties.
 #[derive(Default)]
@@ -15,8 +23,33 @@ pub struct FileFilter {
     pub size_filter: SizeFilter,
 
 
+    /// If present, only files with a name matching the filter will be matched.
     pub name_filter: NameFilter,
 
+    /// If present, only files with one of the extensions will be matched.
+    pub extension_filter: ExtensionFilter,
+
+    /// If present, only regular files with or without an executable bit
+    /// will be matched.
+    pub executable_filter: ExecutableFilter,
+
+    /// If present, only empty files and directories will be matched.
+    pub empty_filter: EmptyFilter,
+
+    /// If present, only files with a mode matching the filter will be matched.
+    pub perm_filter: PermFilter,
+
+    /// If present, only files the current user can access in the given ways
+    /// will be matched.
+    pub access_filter: AccessFilter,
+
+    /// If present, only files with an owner matching the filter will be
+    /// matched.
+    pub owner_filter: OwnerFilter,
+
+    /// If present, only symlinks matching the filter will be matched.
+    pub link_filter: LinkFilter,
+
     /// If present, only files with a date matching the filter will be matched.
     pub date_filter: Option<DateFilter>,
 
@@ -24,51 +57,101 @@ pub struct FileFilter {
 }
 
 impl FileFilter {
-    pub fn match_file(&self, file: &File) -> bool {
-        if !self.name_filter.match_file(file) {
-            return false;
-        }
+    /// Whether the file matches every filter, with the byte ranges of its
+    /// name matched by the name filter, so they can be highlighted without
+    /// running the regex again. The filters are checked from the cheapest
+    /// to the most expensive: first the ones that only need the name and
+    /// the type of the file, then the ones that need its metadata, read
+    /// once, and last the ones that need more system calls.
+    pub fn match_file(&self, file: &File) -> Option<Vec<Range<usize>>> {
+        self.match_file_with(file, None)
+    }
+
+    /// Like `match_file`, for a directory whose emptiness the walk knows
+    /// from having read it.
+    pub fn match_walked_dir(&self, file: &File, empty: bool) -> Option<Vec<Range<usize>>> {
+        self.match_file_with(file, Some(empty))
+    }
 
+    fn match_file_with(&self, file: &File, empty_dir: Option<bool>) -> Option<Vec<Range<usize>>> {
         if self.only_dirs && !file.is_directory() {
-            return false;
+            return None;
         }
 
         if !self.include_dirs && file.is_directory() {
-            return false;
+            return None;
+        }
+
+        let highlights = self.name_filter.match_file(file)?;
+
+        if !self.extension_filter.match_file(file) {
+            return None;
         }
 
         if !self.size_filter.match_file(file) {
-            return false;
+            return None;
         }
 
-        true
+        if !self.executable_filter.match_file(file) {
+            return None;
+        }
+
+        if !self.perm_filter.match_file(file) {
+            return None;
+        }
+
+        if !self.owner_filter.match_file(file) {
+            return None;
+        }
+
+        if let Some(date_filter) = &self.date_filter {
+            if !date_filter.match_file(file) {
+                return None;
+            }
+        }
+
+        if !self.access_filter.match_file(file) {
+            return None;
+        }
+
+        if !self.link_filter.match_file(file) {
+            return None;
+        }
+
+        if !self.empty_filter.match_file(file, empty_dir) {
+            return None;
+        }
+
+        Some(highlights)
     }
 }
 
 
 /// A file name filter. This is used to filter files based on their name.
-/// The filter is a regular expression.
-#[derive(Debug)]
+/// The filter is a regular expression, matched against the bytes of the
+/// name so the names that aren't valid UTF-8 can be matched too.
+#[derive(Debug, Default)]
 pub enum NameFilter {
+    #[default]
     Unfiltered,
     Regex(Regex),
 }
 
 
 impl NameFilter {
-    pub fn match_file(&self, file: &File) -> bool {
+    /// The byte ranges of the name matched by the filter, used to
+    /// highlight the matches, or None if the name doesn't match. Empty
+    /// matches match the name, but are left out of the ranges.
+    pub fn match_file(&self, file: &File) -> Option<Vec<Range<usize>>> {
         match self {
-            Self::Unfiltered => true,
-            Self::Regex(regex) => regex.is_match(&file.name),
-        }
-    }
-}
+            Self::Unfiltered => Some(Vec::new()),
+            Self::Regex(regex) => {
+                let mut matches = regex.find_iter(file.name.as_bytes()).peekable();
+                matches.peek()?;
 
-/// Implement the Default trait for FileNameFilter.
-/// The default value is Unfiltered.
-impl Default for NameFilter {
-    fn default() -> Self {
-        Self::Unfiltered
+                Some(matches.map(|m| m.range()).filter(|range| !range.is_empty()).collect())
+            },
+        }
     }
 }
 
@@ -88,13 +171,451 @@ impl From<Option<Regex>> for NameFilter {
 }
 
 
+/// An extension filter. This is used to filter files based on their
+/// extension, compared without case. Compound extensions such as `tar.gz`
+/// are compared with the end of the file name.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExtensionFilter {
+    /// The lowercased extensions to match, without their leading dot.
+    pub extensions: Vec<String>,
+
+    /// If true, the files without an extension will be matched too.
+    pub no_extension: bool,
+}
+
+impl ExtensionFilter {
+    pub fn match_file(&self, file: &File) -> bool {
+        if self.extensions.is_empty() && !self.no_extension {
+            return true;
+        }
+
+        let ext = match &file.ext {
+            Some(ext) => ext,
+            None => return self.no_extension,
+        };
+
+        let name = file.name.as_bytes().to_ascii_lowercase();
+        self.extensions.iter().any(|expected| {
+            if expected.contains('.') {
+                name.len() > expected.len() + 1
+                    && name.ends_with(expected.as_bytes())
+                    && name[name.len() - expected.len() - 1] == b'.'
+            } else {
+                expected == ext
+            }
+        })
+    }
+
+    /// Add the extensions of a comma-separated list. An empty item selects
+    /// the files without an extension.
+    pub fn add_list(&mut self, list: &str) {
+        for ext in list.split(',') {
+            let ext = ext.trim_start_matches('.');
+
+            if ext.is_empty() {
+                self.no_extension = true;
+            } else {
+                self.extensions.push(ext.to_ascii_lowercase());
+            }
+        }
+    }
+}
+
+/// An executable filter. This is used to filter regular files based on
+/// whether any of their executable bits is set. The mode bits are checked
+/// rather than `access(X_OK)`, so the result doesn't depend on who runs the
+/// search, which the checks for scripts that lost their `+x` bit need.
+/// `access(X_OK)` is the `executable` field of `AccessFilter`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ExecutableFilter {
+    #[default]
+    Unfiltered,
+    Executable,
+    NotExecutable,
+}
+
+impl ExecutableFilter {
+    pub fn match_file(&self, file: &File) -> bool {
+        match self {
+            Self::Unfiltered => true,
+            Self::Executable => file.is_executable_file(),
+            Self::NotExecutable => file.is_file() && !file.is_executable_file(),
+        }
+    }
+}
+
+/// An empty filter. This is used to match the regular files of size 0 and
+/// the directories without entries. The walk knows whether the directories
+/// it reads are empty once it has read them, so it gives their emptiness;
+/// the other directories, such as the ones of `--files-from`, are read.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum EmptyFilter {
+    #[default]
+    Unfiltered,
+
+    /// Only the directories without any entry are empty.
+    Empty,
+
+    /// The directories holding only empty directories are empty too.
+    EmptyRecursive,
+}
+
+impl EmptyFilter {
+    pub fn match_file(&self, file: &File, empty_dir: Option<bool>) -> bool {
+        match self {
+            Self::Unfiltered => true,
+            _ if file.is_file() => file.get_size() == Some(0),
+            _ if !file.is_directory() => false,
+            _ => empty_dir.unwrap_or_else(|| self.read_is_empty(&file.path)),
+        }
+    }
+
+    /// Whether an entry makes the directory holding it non-empty. With
+    /// `--empty-recursive`, a directory entry only does if it isn't empty
+    /// itself, which the walk knows once it has read it.
+    pub fn is_content(&self, file: &File) -> bool {
+        *self != Self::EmptyRecursive || !file.is_directory()
+    }
+
+    /// Whether a directory the walk doesn't read is empty. The directories
+    /// under it are read one at a time, and one that can't be read isn't
+    /// empty.
+    fn read_is_empty(&self, path: &Path) -> bool {
+        let mut pending = vec![path.to_path_buf()];
+
+        while let Some(path) = pending.pop() {
+            let entries = match std::fs::read_dir(&path) {
+                Ok(entries) => entries,
+                Err(_) => return false,
+            };
+
+            for entry in entries {
+                match entry.and_then(|entry| Ok((entry.file_type()?, entry.path()))) {
+                    Ok((file_type, path)) if *self == Self::EmptyRecursive && file_type.is_dir() => pending.push(path),
+                    _ => return false,
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// A permission filter. This is used to filter files based on their mode,
+/// in the style of find's `-perm`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum PermFilter {
+    #[default]
+    Unfiltered,
+
+    /// The permission bits of the file are exactly the given ones.
+    Exact(u32),
+
+    /// All of the given bits are set, written with a `-` prefix.
+    AllOf(u32),
+
+    /// Any of the given bits is set, written with a `/` prefix.
+    AnyOf(u32),
+}
+
+impl PermFilter {
+    /// The permission bits, including the setuid, setgid and sticky bits.
+    const MASK: u32 = 0o7777;
+
+    pub fn match_file(&self, file: &File) -> bool {
+        if let Self::Unfiltered = self {
+            return true;
+        }
+
+        let mode = match file.metadata() {
+            Ok(metadata) => metadata.permissions().mode() & Self::MASK,
+            Err(_) => return false,
+        };
+
+        match self {
+            Self::Unfiltered => true,
+            Self::Exact(bits) => mode == *bits,
+            Self::AllOf(bits) => mode & bits == *bits,
+            Self::AnyOf(bits) => *bits == 0 || mode & bits != 0,
+        }
+    }
+
+    /// Parse a mode, either octal (`4000`, `-0002`) or symbolic
+    /// (`u+x,g-w`), optionally prefixed by `-` or `/`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let (constructor, mode): (fn(u32) -> Self, &str) = match input.as_bytes().first() {
+            Some(b'-') => (Self::AllOf, &input[1..]),
+            Some(b'/') => (Self::AnyOf, &input[1..]),
+            _ => (Self::Exact, input),
+        };
+
+        if mode.is_empty() {
+            return None;
+        }
+
+        let bits = if mode.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+            u32::from_str_radix(mode, 8).ok().filter(|bits| *bits <= Self::MASK)?
+        } else {
+            Self::parse_symbolic(mode)?
+        };
+
+        Some(constructor(bits))
+    }
+
+    /// Parse a symbolic mode the way chmod does, starting from no bits set.
+    /// Without a user, the clause applies to everyone. The clauses are
+    /// sliced by byte, so a mode that isn't ASCII is rejected first.
+    fn parse_symbolic(mode: &str) -> Option<u32> {
+        if !mode.is_ascii() {
+            return None;
+        }
+
+        let mut bits = 0;
+
+        for clause in mode.split(',') {
+            let who_len = clause.bytes().take_while(|b| b"ugoa".contains(b)).count();
+            let (who, mut actions) = clause.split_at(who_len);
+
+            let mut who_mask = who.bytes().fold(0, |mask, b| mask | match b {
+                b'u' => 0o4700,
+                b'g' => 0o2070,
+                b'o' => 0o1007,
+                _    => 0o7777,
+            });
+            if who_mask == 0 {
+                who_mask = 0o7777;
+            }
+
+            if actions.is_empty() {
+                return None;
+            }
+
+            while let Some(op) = actions.bytes().next() {
+                let perms_len = actions[1..].bytes().take_while(|b| b"rwxXst".contains(b)).count();
+                let perms = &actions[1..1 + perms_len];
+                actions = &actions[1 + perms_len..];
+
+                let perm_bits = perms.bytes().fold(0, |mask, b| mask | match b {
+                    b'r' => 0o0444,
+                    b'w' => 0o0222,
+                    b'x' | b'X' => 0o0111,
+                    b's' => 0o6000,
+                    _    => 0o1000,
+                }) & who_mask;
+
+                match op {
+                    b'+' => bits |= perm_bits,
+                    b'-' => bits &= !perm_bits,
+                    b'=' => bits = (bits & !who_mask) | perm_bits,
+                    _    => return None,
+                }
+            }
+        }
+
+        Some(bits)
+    }
+}
+
+/// An access filter. This is used to filter files based on whether the
+/// current user can read, write or execute them, as checked by `access(2)`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct AccessFilter {
+    pub readable: bool,
+    pub writable: bool,
+    pub executable: bool,
+}
+
+impl AccessFilter {
+    pub fn match_file(&self, file: &File) -> bool {
+        let mut mode = 0;
+
+        if self.readable {
+            mode |= libc::R_OK;
+        }
+        if self.writable {
+            mode |= libc::W_OK;
+        }
+        if self.executable {
+            mode |= libc::X_OK;
+        }
+
+        if mode == 0 {
+            return true;
+        }
+
+        let path = match CString::new(file.path.as_os_str().as_bytes()) {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+
+        // SAFETY: the path is a valid nul-terminated string.
+        unsafe { libc::access(path.as_ptr(), mode) == 0 }
+    }
+}
+
+/// An owner filter. This is used to filter files based on their user and
+/// group, or on the lack of an entry for them.
+#[derive(Debug, Default)]
+pub struct OwnerFilter {
+    /// If present, only the files owned by this user id will be matched.
+    pub uid: Option<u32>,
+
+    /// If present, only the files owned by this group id will be matched.
+    pub gid: Option<u32>,
+
+    /// If true, only the files whose user id has no entry will be matched.
+    pub no_user: bool,
+
+    /// If true, only the files whose group id has no entry will be matched.
+    pub no_group: bool,
+
+    /// The names of the users and groups, to check which ids have an entry.
+    pub users: UsersCache,
+}
+
+impl OwnerFilter {
+    pub fn match_file(&self, file: &File) -> bool {
+        if self.uid.is_none() && self.gid.is_none() && !self.no_user && !self.no_group {
+            return true;
+        }
+
+        let (uid, gid) = match file.metadata() {
+            Ok(metadata) => (metadata.uid(), metadata.gid()),
+            Err(_) => return false,
+        };
+
+        if self.uid.map_or(false, |expected| expected != uid) {
+            return false;
+        }
+
+        if self.gid.map_or(false, |expected| expected != gid) {
+            return false;
+        }
+
+        if self.no_user && self.users.user_name(uid).is_some() {
+            return false;
+        }
+
+        if self.no_group && self.users.group_name(gid).is_some() {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// A symlink filter. This is used to filter symlinks based on their target.
+/// When it's used, only symlinks can be matched.
+#[derive(Debug, Default)]
+pub struct LinkFilter {
+    /// If true, only the symlinks whose target doesn't exist will be matched.
+    pub broken: bool,
+
+    /// If present, only the symlinks whose target path matches the regular
+    /// expression will be matched.
+    pub target: Option<Regex>,
+}
+
+impl LinkFilter {
+    pub fn match_file(&self, file: &File) -> bool {
+        if !self.broken && self.target.is_none() {
+            return true;
+        }
+
+        if !file.is_link() {
+            return false;
+        }
+
+        let target = file.link_target();
+
+        if self.broken && !target.is_broken() {
+            return false;
+        }
+
+        match (&self.target, target.path()) {
+            (Some(regex), Some(path)) => regex.is_match(path.as_os_str().as_bytes()),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// A file date filter. This is used to filter files based on one of their
+/// timestamps, compared with the same timestamp of a reference file.
 #[derive(Debug)]
-pub enum DateFilter {
+pub struct DateFilter {
+    /// The timestamp that is compared.
+    pub time: TimeField,
+
+    /// If present, only files more recent than this time will be matched.
+    pub newer_than: Option<SystemTime>,
+
+    /// If present, only files older than this time will be matched.
+    pub older_than: Option<SystemTime>,
+}
+
+impl DateFilter {
+    pub fn match_file(&self, file: &File) -> bool {
+        let time = match file.metadata().and_then(|metadata| self.time.of(&metadata)) {
+            Ok(time) => time,
+            Err(_) => return false,
+        };
+
+        if self.newer_than.map_or(false, |newer_than| time <= newer_than) {
+            return false;
+        }
+
+        if self.older_than.map_or(false, |older_than| time >= older_than) {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// The timestamps of a file.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum TimeField {
+    /// The last time the contents were modified.
+    #[default]
+    Modified,
+
+    /// The last time the contents were read.
+    Accessed,
+
+    /// The last time the metadata changed.
+    Changed,
+
+    /// The time the file was created, if the filesystem records it.
+    Created,
+}
+
+impl TimeField {
+    /// The timestamp of the given metadata.
+    pub fn of(self, metadata: &std::fs::Metadata) -> std::io::Result<SystemTime> {
+        match self {
+            Self::Modified => metadata.modified(),
+            Self::Accessed => metadata.accessed(),
+            Self::Created  => metadata.created(),
+            Self::Changed  => {
+                let seconds = Duration::from_secs(metadata.ctime().unsigned_abs());
+                let nanoseconds = Duration::from_nanos(metadata.ctime_nsec().unsigned_abs());
+
+                if metadata.ctime() >= 0 {
+                    Ok(UNIX_EPOCH + seconds + nanoseconds)
+                } else {
+                    Ok(UNIX_EPOCH - seconds + nanoseconds)
+                }
+            },
+        }
+    }
 }
 
 /// A file size filter. This is used to filter files based on their size.
 /// The filter is a comparison between the file size and a given value.
+#[derive(Default)]
 pub enum SizeFilter {
+    #[default]
     Unfiltered,
     Equal(u64),
     Inferior(u64),
@@ -105,13 +626,22 @@ pub enum SizeFilter {
 
 impl SizeFilter {
     pub fn match_file(&self, file: &File) -> bool {
+        if let Self::Unfiltered = self {
+            return true;
+        }
+
+        let file_size = match file.get_size() {
+            Some(file_size) => file_size,
+            None => return false,
+        };
+
         match self {
             Self::Unfiltered => true,
-            Self::Equal(size) if file.get_size() == *size => true,
-            Self::Inferior(size) if file.get_size() < *size => true,
-            Self::Superior(size) if file.get_size() > *size => true,
-            Self::InferiorOrEqual(size) if file.get_size() <= *size => true,
-            Self::SuperiorOrEqual(size) if file.get_size() >= *size => true,
+            Self::Equal(size) if file_size == *size => true,
+            Self::Inferior(size) if file_size < *size => true,
+            Self::Superior(size) if file_size > *size => true,
+            Self::InferiorOrEqual(size) if file_size <= *size => true,
+            Self::SuperiorOrEqual(size) if file_size >= *size => true,
             _ => false,
         }
     }
@@ -135,19 +665,17 @@ impl SizeFilter {
     }
 
     fn parse_size(unit: &Option<FileSizeUnit>, bytes: &[u8]) -> u64 {
-        let size: &[u8];
-
-        if bytes.starts_with(b"+=") || bytes.starts_with(b"-=") {
-            size = &bytes[2..]
+        let size = if bytes.starts_with(b"+=") || bytes.starts_with(b"-=") {
+            &bytes[2..]
         } else {
-            size = &bytes[1..]
-        }
+            &bytes[1..]
+        };
 
         let size = match unit {
             Some(_) => {
                 &size[..size.len() - 1]
             }
-            None => &size,
+            None => size,
         };
 
         String::from_utf8_lossy(size).to_string().parse::<u64>().unwrap_or(0)
@@ -160,11 +688,8 @@ impl From<&[u8]> for SizeFilter {
         let unit = Self::parse_unit(&bytes[bytes.len() - 1]);
         let mut size = Self::parse_size(&unit, bytes);
 
-        match &unit {
-            Some(value) => {
-                size *= value.into_bytes()
-            }
-            None => ()
+        if let Some(value) = &unit {
+            size *= value.into_bytes()
         }
 
         match sign {
@@ -177,12 +702,7 @@ impl From<&[u8]> for SizeFilter {
     }
 }
 
-impl Default for SizeFilter {
-    fn default() -> Self {
-        Self::Unfiltered
-    }
-}
-
+#[derive(Clone, Copy)]
 pub enum FileSizeUnit {
     Byte,
     Kibibyte,
@@ -191,7 +711,7 @@ pub enum FileSizeUnit {
 }
 
 impl FileSizeUnit {
-    fn into_bytes(&self) -> u64 {
+    fn into_bytes(self) -> u64 {
         match self {
             Self::Byte => 1,
             Self::Kibibyte => 1024,
@@ -221,4 +741,333 @@ impl ComparisonSign {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod perm_filter_test {
+    use super::PermFilter::{self, *};
+
+    macro_rules! test_perm {
+        ($fn_name:ident: $input:expr => $expected:expr) => {
+            #[test]
+            fn $fn_name() {
+                assert_eq!(PermFilter::parse($input), $expected);
+            }
+        };
+    }
+
+    test_perm!(octal: "644" => Some(Exact(0o644)));
+    test_perm!(octal_setuid: "4000" => Some(Exact(0o4000)));
+    test_perm!(octal_all_of: "-0002" => Some(AllOf(0o2)));
+    test_perm!(octal_any_of: "/111" => Some(AnyOf(0o111)));
+    test_perm!(octal_too_big: "17777" => None);
+
+    test_perm!(symbolic_user: "u+x" => Some(Exact(0o100)));
+    test_perm!(symbolic_everyone: "+w" => Some(Exact(0o222)));
+    test_perm!(symbolic_clauses: "u+rwx,g+rx,g-x" => Some(Exact(0o740)));
+    test_perm!(symbolic_all_of: "-o+w" => Some(AllOf(0o002)));
+    test_perm!(symbolic_setuid: "/u+s" => Some(AnyOf(0o4000)));
+    test_perm!(symbolic_sticky: "+t" => Some(Exact(0o1000)));
+    test_perm!(symbolic_set: "a+rwx,go=r" => Some(Exact(0o744)));
+    test_perm!(symbolic_several_ops: "u+r-r+w" => Some(Exact(0o200)));
+
+    test_perm!(empty: "" => None);
+    test_perm!(only_prefix: "-" => None);
+    test_perm!(no_op: "u" => None);
+    test_perm!(bad_perm: "u+q" => None);
+    test_perm!(bad_digit: "648" => None);
+    test_perm!(non_ascii: "é" => None);
+    test_perm!(non_ascii_perm: "u+é" => None);
+    test_perm!(non_ascii_who: "é+x" => None);
+    test_perm!(non_ascii_after_prefix: "/é" => None);
+
+    use std::path::Path;
+
+    use crate::fs::file::File;
+    use crate::fs::test::TempDir;
+
+    fn matches(filter: &str, path: &Path) -> bool {
+        PermFilter::parse(filter).unwrap().match_file(&File::from_args(path.to_path_buf(), None, None).unwrap())
+    }
+
+    #[test]
+    fn exact_mode() {
+        let temp = TempDir::new("perm-exact");
+        let file = temp.file("file", "", 0o644);
+
+        assert!(matches("644", &file));
+        assert!(matches("u+rw,go+r", &file));
+        assert!(!matches("640", &file));
+        assert!(!matches("u+rw", &file));
+    }
+
+    #[test]
+    fn all_of_mode() {
+        let temp = TempDir::new("perm-all-of");
+        let world_writable = temp.file("world-writable", "", 0o666);
+        let private = temp.file("private", "", 0o600);
+
+        assert!(matches("-0002", &world_writable));
+        assert!(matches("-o+w", &world_writable));
+        assert!(matches("-u+rw,o+rw", &world_writable));
+        assert!(!matches("-0002", &private));
+        assert!(!matches("-u+x", &world_writable));
+    }
+
+    #[test]
+    fn any_of_mode() {
+        let temp = TempDir::new("perm-any-of");
+        let setuid = temp.file("setuid", "", 0o4755);
+        let group_exec = temp.file("group-exec", "", 0o610);
+        let data = temp.file("data", "", 0o644);
+
+        assert!(matches("/4000", &setuid));
+        assert!(matches("/u+s", &setuid));
+        assert!(!matches("/u+s", &data));
+        assert!(matches("/111", &group_exec));
+        assert!(!matches("/111", &data));
+        assert!(matches("/0", &data));
+    }
+}
+
+#[cfg(test)]
+mod access_filter_test {
+    use super::*;
+
+    use crate::fs::test::TempDir;
+
+    fn matches(filter: AccessFilter, path: &Path) -> bool {
+        filter.match_file(&File::from_args(path.to_path_buf(), None, None).unwrap())
+    }
+
+    const READABLE: AccessFilter = AccessFilter { readable: true, writable: false, executable: false };
+    const WRITABLE: AccessFilter = AccessFilter { readable: false, writable: true, executable: false };
+    const EXECUTABLE: AccessFilter = AccessFilter { readable: false, writable: false, executable: true };
+
+    #[test]
+    fn access() {
+        let temp = TempDir::new("access-filter");
+        let script = temp.file("script", "", 0o700);
+        let data = temp.file("data", "", 0o600);
+        let locked = temp.file("locked", "", 0o000);
+
+        assert!(matches(AccessFilter::default(), &locked));
+
+        assert!(matches(READABLE, &data));
+        assert!(matches(WRITABLE, &data));
+        assert!(matches(AccessFilter { readable: true, writable: true, executable: false }, &data));
+        assert!(matches(EXECUTABLE, &script));
+
+        // Even root needs an executable bit to execute a file.
+        assert!(!matches(EXECUTABLE, &data));
+        assert!(!matches(EXECUTABLE, &locked));
+
+        // Root can read and write anything, so it can't be checked as root.
+        // SAFETY: `geteuid` has no preconditions.
+        if unsafe { libc::geteuid() } != 0 {
+            assert!(!matches(READABLE, &locked));
+            assert!(!matches(WRITABLE, &locked));
+        }
+    }
+}
+
+#[cfg(test)]
+mod name_filter_test {
+    use super::*;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    fn spans(regex: &str, name: &str) -> Option<Vec<Range<usize>>> {
+        let file = File::from_args(PathBuf::from("/"), None, OsString::from(name)).unwrap();
+        NameFilter::Regex(Regex::new(regex).unwrap()).match_file(&file)
+    }
+
+    #[test]
+    fn every_match_is_highlighted() {
+        assert_eq!(spans("a", "banana"), Some(vec![1..2, 3..4, 5..6]));
+    }
+
+    #[test]
+    fn empty_matches_match_without_highlights() {
+        assert_eq!(spans("^", "banana"), Some(vec![]));
+    }
+
+    #[test]
+    fn no_match() {
+        assert_eq!(spans("z", "banana"), None);
+    }
+}
+
+#[cfg(test)]
+mod link_filter_test {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    use crate::fs::test::TempDir;
+
+    #[test]
+    fn broken_links_and_targets() {
+        let temp = TempDir::new("link-filter");
+        let dir = &temp.0;
+        temp.file("file", "", 0o644);
+        symlink("file", dir.join("good")).unwrap();
+        symlink("missing", dir.join("broken")).unwrap();
+
+        let matches = |filter: &LinkFilter, name: &str| {
+            filter.match_file(&File::from_args(dir.join(name), None, None).unwrap())
+        };
+
+        let broken = LinkFilter { broken: true, target: None };
+        assert!(matches(&broken, "broken"));
+        assert!(!matches(&broken, "good"));
+        assert!(!matches(&broken, "file"));
+
+        let target = LinkFilter { broken: false, target: Some(Regex::new("^fi").unwrap()) };
+        assert!(matches(&target, "good"));
+        assert!(!matches(&target, "broken"));
+        assert!(!matches(&target, "file"));
+
+        let broken_target = LinkFilter { broken: true, target: Some(Regex::new("miss").unwrap()) };
+        assert!(matches(&broken_target, "broken"));
+        assert!(!matches(&broken_target, "good"));
+
+        let unfiltered = LinkFilter::default();
+        assert!(matches(&unfiltered, "file"));
+    }
+}
+
+#[cfg(test)]
+mod executable_filter_test {
+    use super::*;
+    use std::path::PathBuf;
+
+    use crate::fs::test::TempDir;
+
+    #[test]
+    fn executable_bits() {
+        let temp = TempDir::new("executable-filter");
+        let matches = |filter: ExecutableFilter, path: PathBuf| {
+            filter.match_file(&File::from_args(path, None, None).unwrap())
+        };
+
+        let script = temp.file("script", "", 0o755);
+        let user_only = temp.file("user-only", "", 0o700);
+        let group_only = temp.file("group-only", "", 0o610);
+        let data = temp.file("data", "", 0o644);
+        let dir = temp.dir("dir");
+
+        assert!(matches(ExecutableFilter::Executable, script.clone()));
+        assert!(matches(ExecutableFilter::Executable, user_only));
+        assert!(matches(ExecutableFilter::Executable, group_only));
+        assert!(!matches(ExecutableFilter::Executable, data.clone()));
+        assert!(!matches(ExecutableFilter::Executable, dir.clone()));
+
+        assert!(matches(ExecutableFilter::NotExecutable, data.clone()));
+        assert!(!matches(ExecutableFilter::NotExecutable, script));
+        assert!(!matches(ExecutableFilter::NotExecutable, dir.clone()));
+
+        assert!(matches(ExecutableFilter::Unfiltered, data));
+        assert!(matches(ExecutableFilter::Unfiltered, dir));
+    }
+}
+
+#[cfg(test)]
+mod extension_filter_test {
+    use super::*;
+
+    use crate::fs::test::TempDir;
+
+    fn matches(list: &str, temp: &TempDir, name: &str) -> bool {
+        let mut filter = ExtensionFilter::default();
+        filter.add_list(list);
+
+        let path = temp.file(name, "", 0o644);
+        filter.match_file(&File::from_args(path, None, None).unwrap())
+    }
+
+    #[test]
+    fn compound_extensions() {
+        let temp = TempDir::new("extension-compound");
+
+        assert!(matches("tar.gz", &temp, "a.tar.gz"));
+        assert!(!matches("tar.gz", &temp, "tar.gz"));
+        assert!(!matches("tar.gz", &temp, "a.gz"));
+        assert!(!matches("tar.gz", &temp, "atar.gz"));
+        assert!(matches("gz", &temp, "a.tar.gz"));
+    }
+
+    #[test]
+    fn case_folding() {
+        let temp = TempDir::new("extension-case");
+
+        assert!(matches("rs", &temp, "MAIN.RS"));
+        assert!(matches("RS", &temp, "main.rs"));
+        assert!(matches("Tar.GZ", &temp, "a.TAR.gz"));
+    }
+
+    #[test]
+    fn no_extension() {
+        let temp = TempDir::new("extension-none");
+
+        assert!(matches(",", &temp, "Makefile"));
+        assert!(matches("rs,", &temp, "Makefile"));
+        assert!(matches("rs,", &temp, "main.rs"));
+        assert!(!matches(",", &temp, "main.rs"));
+        assert!(!matches("rs", &temp, "Makefile"));
+
+        assert!(matches(",", &temp, ".bashrc"));
+        assert!(matches(",", &temp, ".gitignore"));
+        assert!(matches(",", &temp, "foo."));
+        assert!(!matches("bashrc", &temp, ".bashrc"));
+        assert!(matches("rs", &temp, ".hidden.rs"));
+        assert!(!matches(",", &temp, ".hidden.rs"));
+    }
+
+    #[test]
+    fn lists() {
+        let temp = TempDir::new("extension-lists");
+
+        assert!(matches("rs,toml", &temp, "Cargo.toml"));
+        assert!(matches(".rs", &temp, "main.rs"));
+        assert!(!matches("rs,toml", &temp, "README.md"));
+    }
+}
+
+#[cfg(test)]
+mod date_filter_test {
+    use super::*;
+
+    use crate::fs::test::TempDir;
+
+    #[test]
+    fn time_fields() {
+        let temp = TempDir::new("time-fields");
+        let metadata = std::fs::symlink_metadata(temp.file("file", "", 0o644)).unwrap();
+
+        assert_eq!(TimeField::Modified.of(&metadata).unwrap(), metadata.modified().unwrap());
+        assert_eq!(TimeField::Accessed.of(&metadata).unwrap(), metadata.accessed().unwrap());
+
+        let changed = TimeField::Changed.of(&metadata).unwrap().duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(changed.as_secs(), metadata.ctime() as u64);
+        assert_eq!(changed.subsec_nanos(), metadata.ctime_nsec() as u32);
+    }
+
+    #[test]
+    fn bounds_are_exclusive() {
+        let temp = TempDir::new("date-filter");
+        let file = File::from_args(temp.file("file", "", 0o644), None, None).unwrap();
+        let time = file.metadata().unwrap().modified().unwrap();
+        let second = Duration::from_secs(1);
+
+        let matches = |newer_than, older_than| {
+            DateFilter { time: TimeField::Modified, newer_than, older_than }.match_file(&file)
+        };
+
+        assert!(matches(Some(time - second), None));
+        assert!(!matches(Some(time), None));
+        assert!(matches(None, Some(time + second)));
+        assert!(!matches(None, Some(time)));
+        assert!(matches(Some(time - second), Some(time + second)));
+        assert!(!matches(Some(time + second), Some(time - second)));
+    }
+}
@@ -0,0 +1,202 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// The file listing the users of the system.
+static PASSWD: &str = "/etc/passwd";
+
+/// The file listing the groups of the system.
+static GROUP: &str = "/etc/group";
+
+/// A cache of the user and group names, read from `/etc/passwd` and
+/// `/etc/group` without going through NSS. Each id and each name is looked
+/// up only once, whether it has an entry or not.
+#[derive(Debug)]
+pub struct UsersCache {
+    passwd: PathBuf,
+    group: PathBuf,
+
+    users: RefCell<HashMap<u32, Option<String>>>,
+    groups: RefCell<HashMap<u32, Option<String>>>,
+
+    uids: RefCell<HashMap<String, Option<u32>>>,
+    gids: RefCell<HashMap<String, Option<u32>>>,
+}
+
+impl Default for UsersCache {
+    fn default() -> Self {
+        Self::new(PASSWD, GROUP)
+    }
+}
+
+impl UsersCache {
+    pub fn new<P: Into<PathBuf>, G: Into<PathBuf>>(passwd: P, group: G) -> Self {
+        Self {
+            passwd: passwd.into(),
+            group: group.into(),
+            users: RefCell::default(),
+            groups: RefCell::default(),
+            uids: RefCell::default(),
+            gids: RefCell::default(),
+        }
+    }
+
+    /// The name of the user with the given id, if it has an entry.
+    pub fn user_name(&self, uid: u32) -> Option<String> {
+        self.users.borrow_mut()
+            .entry(uid)
+            .or_insert_with(|| find_entry(&self.passwd, |entry| entry.id == uid).map(|entry| entry.name))
+            .clone()
+    }
+
+    /// The name of the group with the given id, if it has an entry.
+    pub fn group_name(&self, gid: u32) -> Option<String> {
+        self.groups.borrow_mut()
+            .entry(gid)
+            .or_insert_with(|| find_entry(&self.group, |entry| entry.id == gid).map(|entry| entry.name))
+            .clone()
+    }
+
+    /// The id of the user with the given name, if it has an entry.
+    pub fn uid_by_name(&self, name: &str) -> Option<u32> {
+        id_by_name(&self.passwd, name, &self.uids, &self.users)
+    }
+
+    /// The id of the group with the given name, if it has an entry.
+    pub fn gid_by_name(&self, name: &str) -> Option<u32> {
+        id_by_name(&self.group, name, &self.gids, &self.groups)
+    }
+}
+
+/// Look up the id of a name in the cache, or in the file. The name of the
+/// id found is cached too.
+fn id_by_name(path: &PathBuf, name: &str, ids: &RefCell<HashMap<String, Option<u32>>>, names: &RefCell<HashMap<u32, Option<String>>>) -> Option<u32> {
+    if let Some(id) = ids.borrow().get(name) {
+        return *id;
+    }
+
+    let id = find_entry(path, |entry| entry.name == name).map(|entry| entry.id);
+    ids.borrow_mut().insert(name.to_string(), id);
+
+    if let Some(id) = id {
+        names.borrow_mut().entry(id).or_insert_with(|| Some(name.to_string()));
+    }
+
+    id
+}
+
+/// The name and id of a line of `/etc/passwd` or `/etc/group`, which both
+/// start with `name:password:id:`.
+#[derive(PartialEq, Debug)]
+struct Entry {
+    name: String,
+    id: u32,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split(':');
+
+        let name = fields.next().filter(|name| !name.is_empty() && !name.starts_with('#'))?;
+        let id = fields.nth(1)?.parse().ok()?;
+
+        Some(Self { name: name.to_string(), id })
+    }
+}
+
+/// Find the first entry of the file matching the predicate. A file that
+/// can’t be read has no entries.
+fn find_entry<P: Fn(&Entry) -> bool>(path: &PathBuf, predicate: P) -> Option<Entry> {
+    let contents = fs::read_to_string(path).ok()?;
+
+    contents.lines()
+        .filter_map(Entry::parse)
+        .find(|entry| predicate(entry))
+}
+
+#[cfg(test)]
+mod users_test {
+    use super::*;
+
+    #[test]
+    fn passwd_line() {
+        let entry = Entry::parse("root:x:0:0:root:/root:/bin/bash");
+        assert_eq!(entry, Some(Entry { name: String::from("root"), id: 0 }));
+    }
+
+    #[test]
+    fn group_line() {
+        let entry = Entry::parse("wheel:x:10:alice,bob");
+        assert_eq!(entry, Some(Entry { name: String::from("wheel"), id: 10 }));
+    }
+
+    #[test]
+    fn comment_line() {
+        assert_eq!(Entry::parse("# root:x:0:0"), None);
+    }
+
+    #[test]
+    fn truncated_line() {
+        assert_eq!(Entry::parse("root:x"), None);
+    }
+
+    #[test]
+    fn invalid_id() {
+        assert_eq!(Entry::parse("root:x:zero:0"), None);
+    }
+
+    use crate::fs::test::TempDir;
+
+    fn fixture(temp: &TempDir) -> UsersCache {
+        let passwd = temp.file("passwd", "root:x:0:0:root:/root:/bin/sh\n# comment\nalice:x:1000:1000::/home/alice:/bin/sh\n1001:x:1002:1002::/:/bin/sh\n", 0o644);
+        let group = temp.file("group", "root:x:0:\nstaff:x:50:alice\n", 0o644);
+        UsersCache::new(passwd, group)
+    }
+
+    #[test]
+    fn names_by_id() {
+        let temp = TempDir::new("users-names");
+        let users = fixture(&temp);
+
+        assert_eq!(users.user_name(1000), Some(String::from("alice")));
+        assert_eq!(users.user_name(4242), None);
+        assert_eq!(users.group_name(50), Some(String::from("staff")));
+        assert_eq!(users.group_name(1000), None);
+    }
+
+    #[test]
+    fn ids_by_name() {
+        let temp = TempDir::new("users-ids");
+        let users = fixture(&temp);
+
+        assert_eq!(users.uid_by_name("alice"), Some(1000));
+        assert_eq!(users.uid_by_name("1001"), Some(1002));
+        assert_eq!(users.uid_by_name("bob"), None);
+        assert_eq!(users.gid_by_name("staff"), Some(50));
+    }
+
+    #[test]
+    fn lookups_are_cached() {
+        let temp = TempDir::new("users-cache");
+        let users = fixture(&temp);
+
+        assert_eq!(users.uid_by_name("alice"), Some(1000));
+        assert_eq!(users.user_name(0), Some(String::from("root")));
+        assert_eq!(users.user_name(4242), None);
+
+        std::fs::remove_file(temp.0.join("passwd")).unwrap();
+
+        assert_eq!(users.uid_by_name("alice"), Some(1000));
+        assert_eq!(users.user_name(1000), Some(String::from("alice")));
+        assert_eq!(users.user_name(0), Some(String::from("root")));
+        assert_eq!(users.user_name(4242), None);
+    }
+
+    #[test]
+    fn missing_files_have_no_entries() {
+        let users = UsersCache::new("/nonexistent/passwd", "/nonexistent/group");
+        assert_eq!(users.user_name(0), None);
+        assert_eq!(users.gid_by_name("root"), None);
+    }
+}
@@ -1,20 +1,18 @@
-use std::collections::VecDeque;
-use std::path::PathBuf;
-use std::{env, io};
-use std::io::{Write, ErrorKind};
-use std::ffi::OsStr;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{env, fmt, io};
+use std::io::{ErrorKind, Write};
+use std::ffi::{OsStr, OsString};
 
 mod fs;
 use output::lines;
 
-use crate::fs::{file::File, dir::Dir};
+use crate::fs::{file::File, dir::Dir, filter::EmptyFilter};
 
 mod options;
-use crate::options::errors::OptionsError;
-use crate::options::{Options, OptionsResult};
+use crate::options::{Options, OptionsResult, Vars};
 
 mod output;
-use crate::output::file_path::Options as FileStyle;
 
 mod theme;
 use crate::theme::Theme;
@@ -23,16 +21,29 @@ fn main() {
     use std::process::exit;
 
     let args: Vec<_> = env::args_os().skip(1).collect();
-    match Options::parse(args.iter().map(std::convert::AsRef::as_ref)) {
-        OptionsResult::Ok(options, mut input_paths) => {
+    match Options::parse(args.iter().map(std::convert::AsRef::as_ref), &LiveVars) {
+        OptionsResult::Ok(mut options, mut input_paths) => {
             
-            if input_paths.is_empty() {
+            if input_paths.is_empty() && options.files_from.is_none() {
                 input_paths = vec![ OsStr::new(".") ];
             }
             
             let writer = io::stdout();
+            if let Some(dir) = options.base_directory.clone() {
+                options.anchor_paths(&env::current_dir().unwrap_or_default());
 
-            let search = Search { input_paths, options, writer };
+                if let Err(e) = env::set_current_dir(&dir) {
+                    let _ = options.report.write(&mut io::stderr(), &dir, &e);
+                    exit(exits::FATAL_ERROR);
+                }
+            }
+
+            let isatty = stdout_isatty();
+            let theme = options.theme.to_theme(isatty);
+            options.file_style.hyperlinks = options.hyperlinks.to_hyperlinks(isatty);
+            options.file_style.current_dir = env::current_dir().unwrap_or_default();
+
+            let search = Search { input_paths, options, writer, theme };
 
             match search.run() {
                 Ok(status_code) => {
@@ -42,30 +53,32 @@ fn main() {
                     exit(exits::SUCCESS);
                 },
                 Err(e) => {
-                    eprintln!("{}", e);
-                    exit(exits::RUNTIME_ERROR);
+                    eprintln!("search: {}", e);
+                    exit(exits::FATAL_ERROR);
                 }
             };
         }
         OptionsResult::Help(help_text) => {
-            print!("{}", help_text);
+            exit(print(help_text));
+        },
+        OptionsResult::Version(version) => {
+            exit(print(version));
+        },
+        OptionsResult::Man(man_page) => {
+            exit(print(man_page));
+        },
+        OptionsResult::Completions(completions) => {
+            exit(print(completions));
         },
         OptionsResult::InvalidOptions(e) => {
-            match e {
-                OptionsError::ArgumentNeedsValue(arg) => print!("Argument {} needs a value", arg),
-                OptionsError::BadArgument(arg, os_str) => print!("Bad argument for flag {}. Arg passed : {}", arg, os_str.to_str().unwrap_or("Error unsupported os_str")),
-                OptionsError::Duplicate(flag_1, flag_2) => print!("Duplicated flags : {} {}", flag_1, flag_2),
-                OptionsError::OptionsConflit(arg_1, arg_2) => print!("Conflit between args : {} {}", arg_1, arg_2),
-                OptionsError::ParseError(e) => print!("{:?}", e),
-            }
-            println!("");
-            exit(1);
+            eprintln!("search: {}", e);
+            exit(exits::FATAL_ERROR);
         },
     }
 }
 
 /// The main struct that represents the search.
-pub struct Search<'args> {
+pub struct Search<'args, W> {
     /// List of the free command-line arguments that should correspond to file
     /// names (anything that isn’t an option).
     pub input_paths: Vec<&'args OsStr>,
@@ -74,88 +87,478 @@ pub struct Search<'args> {
     pub options: Options,
 
     /// The writer to use to write the output.
-    pub writer: io::Stdout,
+    pub writer: W,
+
+    /// The theme used to paint the output.
+    pub theme: Theme,
 }
 
-impl <'args> Search<'args> {
+impl <'args, W: Write> Search<'args, W> {
     /// Runs the search.
     /// It iterates over the input paths and searches in each of them.
+    /// The directories, and the symlinks to directories, are walked, while
+    /// the other files, and the paths listed by `--files-from`, are
+    /// filtered as they are.
     /// Each files are filtered by the options given by the user.
     pub fn run(mut self) -> io::Result<i32> {
-        // Stores all dirs to search in
-        let mut dirs: Vec<Dir> = Vec::new();
-        let mut exit_status: i32 = exits::SUCCESS;
+        // Stores the paths of all dirs to search in
+        let mut roots: Vec<PathBuf> = Vec::new();
+        // Stores the files that are matched without being walked
+        let mut files: Vec<File<'_>> = Vec::new();
+        let mut had_errors = false;
 
         for dir_path in &self.input_paths {
             match File::from_args(PathBuf::from(dir_path), None, None) {
-                Err(_e) => {
-                    exit_status = exits::RUNTIME_ERROR;
+                Err(e) => {
+                    had_errors |= self.report_error(dir_path, &e)?;
                 },
                 Ok(f) => {
-                    if f.is_directory() {
-                        match f.to_dir() {
-                            Ok(d) => {
-                                dirs.push(d);
-                            },
-                            Err(e) => writeln!(io::stderr(), "{:?}: {}", dir_path, e)?,
-                        }
+                    if f.points_to_directory() {
+                        roots.push(f.path);
                     } else {
-                        exit_status = exits::OPTIONS_ERROR;
+                        files.push(f);
+                    }
+                },
+            }
+        }
+
+        if let Some(files_from) = &self.options.files_from {
+            match files_from.read() {
+                Ok(paths) => {
+                    for path in paths {
+                        match File::from_args(path.clone(), None, None) {
+                            Ok(f) => files.push(f),
+                            Err(e) => had_errors |= self.report_error(&path, &e)?,
+                        }
                     }
                 },
+                Err(e) => had_errors |= self.report_error(files_from.name(), &e)?,
             }
         }
 
-        self.print_matched_files(dirs, exit_status)
+        self.print_matched_files(files, roots, had_errors)
     }
 
-    /// Prints the matched files.
-    pub fn print_matched_files(&mut self, dirs: Vec<Dir>, exit_status: i32) -> io::Result<i32> {
-        let mut queue: VecDeque<Dir> = VecDeque::from(dirs);
+    /// Prints the matched files, and returns the exit code of the search.
+    /// The given files are matched first, then the roots are walked.
+    /// The search stops early once the limits given by the user are reached.
+    ///
+    /// The walk is depth-first, and only one directory is open at a time: a
+    /// directory is read to its end, and closed, before its subdirectories
+    /// are walked. Only the paths of the subdirectories left to walk are
+    /// kept, so the memory used doesn't grow with the number of files of
+    /// the directories, and the number of open files doesn't grow with the
+    /// depth of the tree.
+    ///
+    /// With an empty filter, whether a directory is empty is only known
+    /// once it has been read, and with `--empty-recursive` once the ones
+    /// under it have been too. So the directories are matched when they are
+    /// left rather than when they are found, and their emptiness is passed
+    /// up to their parent.
+    pub fn print_matched_files(&mut self, files: Vec<File<'_>>, roots: Vec<PathBuf>, mut had_errors: bool) -> io::Result<i32> {
+        let stop_after = self.options.limits.stop_after();
+        let mut match_count = 0;
+
+        let mut matched_files = Vec::new();
+
+        for file in files {
+            if let Some(highlights) = self.options.filter.match_file(&file) {
+                matched_files.push((file, highlights));
+                match_count += 1;
+
+                if stop_after.map_or(false, |limit| match_count >= limit) {
+                    break;
+                }
+            }
+        }
+
+        self.render(matched_files)?;
 
-        while let Some(dir) = VecDeque::pop_front(&mut queue) {
-            let mut matched_files: Vec<File> = Vec::new();
+        let empty_filter = self.options.filter.empty_filter;
+        let mut roots = roots.into_iter();
 
-            for file_result in dir.files() {
-                match file_result {
-                    Ok(file) => {
-                        if file.is_directory() {
-                            match file.to_dir() {
-                                Ok(dir) => queue.push_back(dir),
-                                Err(e) => return Err(e)
+        // The directories between the root and the current one.
+        let mut stack: Vec<Frame> = Vec::new();
+
+        while stop_after.map_or(true, |limit| match_count < limit) {
+            let path = match stack.last_mut() {
+                Some(frame) => match frame.pending.pop() {
+                    Some(path) => path,
+                    None => {
+                        let frame = stack.pop().unwrap();
+
+                        // The roots themselves are never matched.
+                        if let Some(parent) = stack.last_mut() {
+                            parent.empty &= frame.empty;
+
+                            if empty_filter != EmptyFilter::Unfiltered {
+                                match_count += self.match_walked_dir(frame.path, frame.empty)?;
                             }
                         }
+                        continue;
+                    },
+                },
+                None => match roots.next() {
+                    Some(path) => path,
+                    None => break,
+                },
+            };
 
-                        if self.options.filter.match_file(&file) {
-                            matched_files.push(file);
-                        }
+            let mut dir = match Dir::read_dir(path.clone()) {
+                Ok(dir) => dir,
+                Err(e) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.empty = false;
+                    }
+                    had_errors |= self.report_error(&path, &e)?;
+                    continue;
+                },
+            };
+
+            let mut frame = Frame { path, pending: Vec::new(), empty: true };
+
+            while stop_after.map_or(true, |limit| match_count < limit) {
+                let file = match dir.next_file() {
+                    Some(Ok(file)) => file,
+                    Some(Err((path, e))) => {
+                        frame.empty = false;
+                        had_errors |= self.report_error(&path, &e)?;
+                        continue;
                     },
-                    Err((path, e)) => writeln!(io::stderr(), "{:?}: {}", path, e)?,
+                    None => break,
+                };
+
+                if empty_filter.is_content(&file) {
+                    frame.empty = false;
+                }
+
+                if file.is_directory() {
+                    frame.pending.push(file.path.clone());
+
+                    // The directories are matched once they're left, when
+                    // their emptiness is known.
+                    if empty_filter != EmptyFilter::Unfiltered {
+                        continue;
+                    }
+                }
+
+                if let Some(highlights) = self.options.filter.match_file(&file) {
+                    match_count += 1;
+                    self.render(vec![(file, highlights)])?;
                 }
             }
 
-            let r = lines::Render { 
-                files: matched_files,
-                theme: &Theme::default_theme(),
-                file_style: &FileStyle::default() };
-            r.render(&mut self.writer)?;
+            // The subdirectories are walked in the order they were read.
+            frame.pending.reverse();
+            stack.push(frame);
+        }
+
+        Ok(exits::status(match_count > 0, had_errors, self.options.limits.quiet))
+    }
+
+    /// Matches a directory the walk has read, and prints it if it matches.
+    /// Returns the number of matches, 0 or 1.
+    fn match_walked_dir(&mut self, path: PathBuf, empty: bool) -> io::Result<usize> {
+        let file = match File::from_args(path, None, None) {
+            Ok(file) => file,
+            Err(_) => return Ok(0),
+        };
+
+        match self.options.filter.match_walked_dir(&file, empty) {
+            Some(highlights) => {
+                self.render(vec![(file, highlights)])?;
+                Ok(1)
+            },
+            None => Ok(0),
+        }
+    }
+
+    /// Prints the given files with their highlights, unless the user asked
+    /// for nothing to be printed.
+    fn render(&mut self, files: Vec<(File<'_>, Vec<Range<usize>>)>) -> io::Result<()> {
+        if self.options.limits.quiet {
+            return Ok(());
+        }
+
+        let r = lines::Render { 
+            files,
+            theme: &self.theme,
+            file_style: &self.options.file_style };
+        r.render(&mut self.writer)
+    }
+
+    /// Prints an error met on a path to the standard error, unless the
+    /// user asked to ignore it. The search goes on after it. Returns
+    /// whether the error counts toward the exit code.
+    fn report_error<P: AsRef<Path>>(&self, path: P, e: &io::Error) -> io::Result<bool> {
+        if self.options.report.is_ignored(e) {
+            return Ok(false);
         }
 
+        self.options.report.write(&mut io::stderr(), path.as_ref(), e)?;
+        Ok(true)
+    }
+}
+
+/// A directory of the walk, between the root and the current directory.
+struct Frame {
+    /// The path of the directory.
+    path: PathBuf,
+
+    /// The paths of the subdirectories left to walk.
+    pending: Vec<PathBuf>,
+
+    /// Whether the directory is empty so far.
+    empty: bool,
+}
+
+/// Prints the text of an option that replaces the search, like the help,
+/// and returns the exit code. Like for the search, a closed pipe isn't an
+/// error, so `search --man | head` doesn't fail.
+fn print<T: fmt::Display>(text: T) -> i32 {
+    match write!(io::stdout().lock(), "{}", text) {
+        Ok(()) => exits::SUCCESS,
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => exits::SUCCESS,
+        Err(e) => {
+            eprintln!("search: {}", e);
+            exits::FATAL_ERROR
+        },
+    }
+}
 
+/// Whether the standard output is a terminal.
+fn stdout_isatty() -> bool {
+    // SAFETY: `isatty` only inspects the file descriptor.
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
 
+/// The environment variables of the running process.
+struct LiveVars;
 
-        Ok(exit_status)
+impl Vars for LiveVars {
+    fn get(&self, name: &'static str) -> Option<OsString> {
+        env::var_os(name)
     }
 }
 
-/// Exit codes for the program.
+/// Exit codes for the program. Like grep, search tells whether a file
+/// matched, and a failure takes precedence over the result:
+///
+///   0  at least one file matched
+///   1  no file matched
+///   2  some paths couldn’t be read, so the results may be incomplete
+///   3  the options are invalid, or the output couldn’t be written
+///
+/// With `--quiet`, a match still exits with 0 when there were errors, as
+/// the answer is known.
 mod exits {
-    /// Exit code for when search runs OK.
+    /// Exit code for when at least one file matched.
     pub const SUCCESS: i32 = 0;
 
-    /// Exit code for when there was at least one I/O error during execution.
-    pub const RUNTIME_ERROR: i32 = 1;
+    /// Exit code for when no file matched.
+    pub const NO_MATCHES: i32 = 1;
+
+    /// Exit code for when there was at least one I/O error during the search.
+    pub const IO_ERROR: i32 = 2;
+
+    /// Exit code for when the command-line options are invalid, or the
+    /// search couldn’t run at all.
+    pub const FATAL_ERROR: i32 = 3;
+
+    /// The exit code of a search that ran to its end.
+    pub fn status(matched: bool, had_errors: bool, quiet: bool) -> i32 {
+        match (matched, had_errors) {
+            (true, false)               => SUCCESS,
+            (true, true) if quiet       => SUCCESS,
+            (_, true)                   => IO_ERROR,
+            (false, false)              => NO_MATCHES,
+        }
+    }
+
+    #[cfg(test)]
+    mod status_test {
+        use super::*;
+
+        #[test]
+        fn statuses() {
+            assert_eq!(status(true,  false, false), SUCCESS);
+            assert_eq!(status(false, false, false), NO_MATCHES);
+            assert_eq!(status(true,  true,  false), IO_ERROR);
+            assert_eq!(status(false, true,  false), IO_ERROR);
+        }
+
+        #[test]
+        fn quiet_statuses() {
+            assert_eq!(status(true,  false, true), SUCCESS);
+            assert_eq!(status(false, false, true), NO_MATCHES);
+            assert_eq!(status(true,  true,  true), SUCCESS);
+            assert_eq!(status(false, true,  true), IO_ERROR);
+        }
+
+        #[test]
+        fn distinct_codes() {
+            let codes = [SUCCESS, NO_MATCHES, IO_ERROR, FATAL_ERROR];
+            for (i, code) in codes.iter().enumerate() {
+                assert!(!codes[i + 1 ..].contains(code));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod search_test {
+    use super::*;
+
+    use crate::fs::test::TempDir;
+
+    /// No environment, so the output is never coloured.
+    struct NoVars;
+
+    impl Vars for NoVars {
+        fn get(&self, _name: &'static str) -> Option<OsString> {
+            None
+        }
+    }
+
+    /// Run a search from the given directory, and return the sorted paths
+    /// it printed, relative to the directory, with its exit code.
+    fn search(temp: &TempDir, args: &[&str]) -> (Vec<String>, i32) {
+        let root = temp.0.to_string_lossy().into_owned();
+        let args = args.iter().map(|arg| arg.replace('@', &root)).collect::<Vec<_>>();
+
+        let (options, input_paths) = match Options::parse(args.iter().map(OsStr::new), &NoVars) {
+            OptionsResult::Ok(options, input_paths) => (options, input_paths),
+            _ => panic!("invalid options {:?}", args),
+        };
+
+        let theme = options.theme.to_theme(false);
+        let mut output = Vec::new();
+        let status = Search { input_paths, options, writer: &mut output, theme }.run().unwrap();
+
+        let prefix = format!("{}/", root);
+        let mut lines = String::from_utf8(output).unwrap()
+            .lines()
+            .map(|line| line.strip_prefix(&prefix).unwrap_or(line).to_string())
+            .collect::<Vec<_>>();
+        lines.sort();
+
+        (lines, status)
+    }
+
+    /// A tree with empty files and directories, and directories that only
+    /// hold empty directories.
+    fn empty_tree(name: &str) -> TempDir {
+        let temp = TempDir::new(name);
+        temp.dir("empty");
+        temp.dir("nested/a/b");
+        temp.dir("mixed/empty");
+        temp.dir("full");
+        temp.file("zero", "", 0o644);
+        temp.file("full/text", "text", 0o644);
+        temp.file("mixed/zero", "", 0o644);
+        temp
+    }
+
+    #[test]
+    fn empty() {
+        let temp = empty_tree("search-empty");
+        let (lines, status) = search(&temp, &["--empty", "@"]);
+
+        assert_eq!(lines, vec!["empty", "mixed/empty", "mixed/zero", "nested/a/b", "zero"]);
+        assert_eq!(status, exits::SUCCESS);
+    }
+
+    #[test]
+    fn empty_recursive() {
+        let temp = empty_tree("search-empty-recursive");
+        let (lines, _) = search(&temp, &["--empty-recursive", "@"]);
+
+        assert_eq!(lines, vec!["empty", "mixed/empty", "mixed/zero", "nested", "nested/a", "nested/a/b", "zero"]);
+    }
+
+    #[test]
+    fn empty_dirs_only() {
+        let temp = empty_tree("search-empty-dirs");
+        let (lines, _) = search(&temp, &["--empty-recursive", "--only-dirs", "@"]);
+
+        assert_eq!(lines, vec!["empty", "mixed/empty", "nested", "nested/a", "nested/a/b"]);
+    }
+
+    #[test]
+    fn empty_with_max_results() {
+        let temp = empty_tree("search-empty-max");
+        let (lines, _) = search(&temp, &["--empty", "--max-results", "2", "@"]);
+
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn empty_files_from_dirs() {
+        let temp = empty_tree("search-empty-files-from");
+        temp.file("list", &format!("{0}/nested\n{0}/full\n{0}/empty\n", temp.0.display()), 0o644);
+
+        assert_eq!(search(&temp, &["--empty", "--files-from", "@/list"]).0, vec!["empty"]);
+        assert_eq!(search(&temp, &["--empty-recursive", "--files-from", "@/list"]).0, vec!["empty", "nested"]);
+    }
 
-    /// Exit code for when the command-line options are invalid.
-    pub const OPTIONS_ERROR: i32 = 3;
+    #[test]
+    fn linked_root() {
+        let temp = TempDir::new("search-linked-root");
+        temp.dir("dir");
+        temp.file("dir/text", "text", 0o644);
+        std::os::unix::fs::symlink(temp.0.join("dir"), temp.0.join("link")).unwrap();
+        std::os::unix::fs::symlink(".", temp.0.join("dir/inner")).unwrap();
+
+        // The root is followed, but not the links found under it.
+        assert_eq!(search(&temp, &["@/link"]).0, vec!["link/inner -> .", "link/text"]);
+    }
+
+    #[test]
+    fn quiet_prints_nothing() {
+        let temp = empty_tree("search-quiet");
+
+        assert_eq!(search(&temp, &["--quiet", "--name", "zero", "@"]), (vec![], exits::SUCCESS));
+        assert_eq!(search(&temp, &["--quiet", "--name", "nothing", "@"]), (vec![], exits::NO_MATCHES));
+    }
+
+    #[test]
+    fn first_match() {
+        let temp = empty_tree("search-first");
+
+        assert_eq!(search(&temp, &["-1", "--name", "zero", "@"]).0.len(), 1);
+        assert_eq!(search(&temp, &["--max-results", "2", "-1", "--name", "zero", "@"]).0.len(), 1);
+        assert_eq!(search(&temp, &["-1", "--max-results", "2", "--name", "zero", "@"]).0.len(), 2);
+    }
+
+    /// The depth of the tree walked under a limit of open files lower
+    /// than it.
+    const DEEP_TREE_DEPTH: usize = 300;
+
+    #[test]
+    fn deep_tree() {
+        let status = std::process::Command::new(env::current_exe().unwrap())
+            .args(["--ignored", "--exact", "search_test::deep_tree_with_few_files_open"])
+            .output()
+            .unwrap();
+
+        assert!(status.status.success(), "{}", String::from_utf8_lossy(&status.stdout));
+    }
+
+    /// Run by `deep_tree` in its own process, as it lowers the limit of
+    /// open files of the process.
+    #[test]
+    #[ignore]
+    fn deep_tree_with_few_files_open() {
+        let temp = TempDir::new("search-deep-tree");
+        let leaf = "d/".repeat(DEEP_TREE_DEPTH) + "leaf";
+        temp.dir(&leaf);
+
+        let limit = libc::rlimit { rlim_cur: 32, rlim_max: 32 };
+        // SAFETY: `setrlimit` only reads the given limit.
+        assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) }, 0);
+
+        let (lines, status) = search(&temp, &["--name", "^leaf$", "--include-dirs", "@"]);
+        assert_eq!(lines, vec![leaf]);
+        assert_eq!(status, exits::SUCCESS);
+    }
 }
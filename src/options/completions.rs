@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::options::errors::OptionsError;
+use crate::options::flags;
+use crate::options::help::values;
+use crate::options::parser::{Arg, MatchedFlags, TakesValue};
+
+/// The name of the binary the completion scripts are registered for.
+static BIN_NAME: &str = "search";
+
+/// The shells we know how to generate a completion script for.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// A struct that represents the completion script of a shell.
+/// The script is generated from the `ALL_ARGS` table, so every flag
+/// known by the parser is completed.
+pub struct Completions {
+    pub shell: Shell,
+}
+
+impl Completions {
+    /// Deduce a Completions from the given matches flags.
+    pub fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        let shell = match matches.get(&flags::COMPLETIONS)? {
+            Some(shell) if shell == "bash" => Shell::Bash,
+            Some(shell) if shell == "zsh" => Shell::Zsh,
+            Some(shell) if shell == "fish" => Shell::Fish,
+            Some(shell) => return Err(OptionsError::BadArgument(&flags::COMPLETIONS, shell.into())),
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self { shell }))
+    }
+
+    fn write_bash(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "_{}() {{", BIN_NAME)?;
+        writeln!(f, "    local cur prev")?;
+        writeln!(f, "    COMPREPLY=()")?;
+        writeln!(f, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+        writeln!(f, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+        writeln!(f)?;
+        writeln!(f, "    case \"$prev\" in")?;
+
+        for arg in args() {
+            if arg.takes_value == TakesValue::Forbidden {
+                continue;
+            }
+
+            writeln!(f, "        {})", names(arg).join("|"))?;
+            match Value::of(arg) {
+                Value::Words(values) => writeln!(f, "            COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )", values.join(" "))?,
+                Value::Files       => writeln!(f, "            COMPREPLY=( $(compgen -f -- \"$cur\") )")?,
+                Value::Directories => writeln!(f, "            COMPREPLY=( $(compgen -d -- \"$cur\") )")?,
+                Value::Users       => writeln!(f, "            COMPREPLY=( $(compgen -u -- \"$cur\") )")?,
+                Value::Groups      => writeln!(f, "            COMPREPLY=( $(compgen -g -- \"$cur\") )")?,
+            }
+            writeln!(f, "            return 0")?;
+            writeln!(f, "            ;;")?;
+        }
+
+        writeln!(f, "    esac")?;
+        writeln!(f)?;
+        writeln!(f, "    case \"$cur\" in")?;
+        writeln!(f, "        -*)")?;
+
+        let all_names = args().flat_map(names).collect::<Vec<_>>();
+        writeln!(f, "            COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )", all_names.join(" "))?;
+        writeln!(f, "            return 0")?;
+        writeln!(f, "            ;;")?;
+        writeln!(f, "    esac")?;
+        writeln!(f)?;
+        writeln!(f, "    COMPREPLY=( $(compgen -d -- \"$cur\") )")?;
+        writeln!(f, "}}")?;
+        writeln!(f)?;
+        writeln!(f, "complete -o filenames -F _{0} {0}", BIN_NAME)
+    }
+
+    fn write_zsh(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#compdef {}", BIN_NAME)?;
+        writeln!(f)?;
+        writeln!(f, "_{}() {{", BIN_NAME)?;
+        writeln!(f, "    _arguments -s -S \\")?;
+
+        for arg in args() {
+            let names = names(arg);
+            let spec = if names.len() > 1 {
+                format!("'({})'{{{}}}'[{}]'", names.join(" "), names.join(","), quote(arg.description))
+            } else {
+                format!("'{}[{}]'", names[0], quote(arg.description))
+            };
+
+            let message = arg.value_name.unwrap_or("value").to_lowercase();
+            let action = match (&arg.takes_value, Value::of(arg)) {
+                (TakesValue::Forbidden, _) => String::new(),
+                (_, Value::Words(values)) => format!("':{}:({})'", message, values.join(" ")),
+                (_, Value::Files)       => format!("':{}:_files'", message),
+                (_, Value::Directories) => format!("':{}:_files -/'", message),
+                (_, Value::Users)       => format!("':{}:_users'", message),
+                (_, Value::Groups)      => format!("':{}:_groups'", message),
+            };
+
+            writeln!(f, "        {}{} \\", spec, action)?;
+        }
+
+        writeln!(f, "        '*:path:_files -/'")?;
+        writeln!(f, "}}")?;
+        writeln!(f)?;
+        writeln!(f, "_{0} \"$@\"", BIN_NAME)
+    }
+
+    fn write_fish(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "complete -c {} -f -a '(__fish_complete_directories)'", BIN_NAME)?;
+
+        for arg in args() {
+            write!(f, "complete -c {}", BIN_NAME)?;
+
+            if let Some(short) = arg.short {
+                write!(f, " -s {}", short as char)?;
+            }
+            write!(f, " -l {} -d '{}'", arg.long, quote(arg.description))?;
+
+            match (&arg.takes_value, Value::of(arg)) {
+                (TakesValue::Forbidden, _) => (),
+                (_, Value::Words(values)) => write!(f, " -x -a '{}'", values.join(" "))?,
+                (_, Value::Files)       => write!(f, " -r -F")?,
+                (_, Value::Directories) => write!(f, " -x -a '(__fish_complete_directories)'")?,
+                (_, Value::Users)       => write!(f, " -x -a '(__fish_complete_users)'")?,
+                (_, Value::Groups)      => write!(f, " -x -a '(__fish_complete_groups)'")?,
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Implement the Display trait for Completions.
+/// This allows us to print the completion script.
+impl fmt::Display for Completions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.shell {
+            Shell::Bash => Self::write_bash(f),
+            Shell::Zsh  => Self::write_zsh(f),
+            Shell::Fish => Self::write_fish(f),
+        }
+    }
+}
+
+/// What the value of a flag is completed with.
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Value {
+    /// One of the words the flag accepts.
+    Words(&'static [&'static str]),
+    Files,
+    Directories,
+    Users,
+    Groups,
+}
+
+impl Value {
+    fn of(arg: &Arg) -> Self {
+        if let Some(values) = values(arg) {
+            Self::Words(values)
+        } else if arg == &flags::OWNER {
+            Self::Users
+        } else if arg == &flags::GROUP {
+            Self::Groups
+        } else if arg == &flags::BASE_DIRECTORY || arg == &flags::RELATIVE_TO {
+            Self::Directories
+        } else {
+            Self::Files
+        }
+    }
+}
+
+/// Escape a string so it can be used between single quotes.
+fn quote(input: &str) -> String {
+    input.replace('\'', "'\\''")
+}
+
+fn args() -> impl Iterator<Item = &'static Arg> {
+    flags::ALL_ARGS.0.iter().copied()
+}
+
+/// Every name an argument can be written with on the command line.
+fn names(arg: &Arg) -> Vec<String> {
+    let mut names = Vec::new();
+
+    if let Some(short) = arg.short {
+        names.push(format!("-{}", short as char));
+    }
+    names.push(format!("--{}", arg.long));
+
+    names
+}
+
+#[cfg(test)]
+mod completions_test {
+    use super::*;
+
+    fn script(shell: Shell) -> String {
+        Completions { shell }.to_string()
+    }
+
+    #[test]
+    fn bash_completes_every_flag() {
+        let script = script(Shell::Bash);
+        for arg in args() {
+            for name in names(arg) {
+                assert!(script.contains(&name), "{} isn’t completed", name);
+            }
+        }
+        assert!(script.ends_with("complete -o filenames -F _search search\n"));
+    }
+
+    #[test]
+    fn bash_completes_values() {
+        let script = script(Shell::Bash);
+        assert!(script.contains("        --completions)\n            COMPREPLY=( $(compgen -W \"bash zsh fish\" -- \"$cur\") )\n"));
+        assert!(script.contains("        -n|--name)\n            COMPREPLY=( $(compgen -f -- \"$cur\") )\n"));
+        assert!(script.contains("        --owner)\n            COMPREPLY=( $(compgen -u -- \"$cur\") )\n"));
+        assert!(script.contains("        --group)\n            COMPREPLY=( $(compgen -g -- \"$cur\") )\n"));
+        assert!(script.contains("        --base-directory)\n            COMPREPLY=( $(compgen -d -- \"$cur\") )\n"));
+    }
+
+    #[test]
+    fn paths_are_directories() {
+        assert!(script(Shell::Bash).contains("    esac\n\n    COMPREPLY=( $(compgen -d -- \"$cur\") )\n}"));
+        assert!(script(Shell::Zsh).contains("        '*:path:_files -/'\n"));
+        assert!(script(Shell::Fish).starts_with("complete -c search -f -a '(__fish_complete_directories)'\n"));
+    }
+
+    #[test]
+    fn zsh_specs() {
+        let script = script(Shell::Zsh);
+        assert!(script.starts_with("#compdef search\n"));
+        assert!(script.contains("'--completions[print the completion script for SHELL]'':shell:(bash zsh fish)' \\\n"));
+        assert!(script.contains("'(-h --help)'{-h,--help}'["));
+        assert!(script.contains("':user:_users' \\\n"));
+    }
+
+    #[test]
+    fn fish_lines() {
+        let script = script(Shell::Fish);
+        assert!(script.contains("complete -c search -l completions -d 'print the completion script for SHELL' -x -a 'bash zsh fish'\n"));
+        assert!(script.contains("complete -c search -s n -l name -d '"));
+        assert!(script.contains(" -l group -d 'only match the files owned by GROUP, a name or a numeric id' -x -a '(__fish_complete_groups)'\n"));
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(quote("don't"), "don'\\''t");
+    }
+}
@@ -1,6 +1,9 @@
 use std::ffi::OsString;
+use std::fmt;
+use std::io;
 
-use crate::options::parser::{Flag, ShortArg, Values, Arg};
+use crate::options::flags;
+use crate::options::parser::{Flag, ShortArg, Values, Arg, TakesValue};
 
 /// Errors that can occur when parsing flags.
 #[derive(Debug, PartialEq)]
@@ -19,6 +22,7 @@ pub enum ParseError {
 }
 
 /// Errors that can occur when parsing options into filters.
+#[derive(Debug)]
 pub enum OptionsError {
     /// When a duplicated flag is found in strict mode.
     Duplicate(Flag, Flag),
@@ -33,5 +37,182 @@ pub enum OptionsError {
     ParseError(ParseError),
 
     /// When there is a conflict between two args.
-    OptionsConflit(&'static Arg, &'static Arg)
-}
\ No newline at end of file
+    OptionsConflit(&'static Arg, &'static Arg),
+
+    /// When the first arg only has an effect with the second one.
+    Useless(&'static Arg, &'static Arg),
+
+    /// When the theme chosen by the user can't be loaded.
+    BadTheme(String),
+
+    /// When the reference file given to a flag can't be read.
+    BadReference(&'static Arg, OsString, io::Error)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NeedsValue { flag, values: None } => write!(f, "Flag {} needs a value", flag),
+            Self::NeedsValue { flag, values: Some(values) } => write!(f, "Flag {} needs a value ({})", flag, Choices(values)),
+            Self::ForbiddenValue { flag } => write!(f, "Flag {} cannot take a value", flag),
+            Self::UnknownShortArgument { short } => {
+                write!(f, "Unknown argument -{}", *short as char)?;
+
+                match suggest_short(*short) {
+                    Some(arg) => write!(f, ", did you mean -{}?", arg.short.unwrap() as char),
+                    None => Ok(()),
+                }
+            },
+            Self::UnknownArgument { arg } => {
+                let arg = arg.to_string_lossy();
+                write!(f, "Unknown argument --{}", arg)?;
+
+                match suggest_long(&arg) {
+                    Some(suggestion) => write!(f, ", did you mean --{}?", suggestion.long),
+                    None => Ok(()),
+                }
+            },
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate(first, second) if first == second => write!(f, "Flag {} was given twice", first),
+            Self::Duplicate(first, second) => write!(f, "Flag {} conflicts with flag {}", first, second),
+            Self::BadArgument(arg, value) => {
+                write!(f, "Bad argument {:?} for flag {}", value, arg)?;
+
+                match arg.takes_value {
+                    TakesValue::Necessary(Some(values)) |
+                    TakesValue::Optional(Some(values)) => write!(f, " ({})", Choices(values)),
+                    _ => Ok(()),
+                }
+            },
+            Self::ArgumentNeedsValue(arg) => write!(f, "Flag {} needs a value", arg),
+            Self::ParseError(e) => write!(f, "{}", e),
+            Self::OptionsConflit(first, second) => write!(f, "Flag {} conflicts with flag {}", first, second),
+            Self::Useless(arg, needed) => write!(f, "Flag {} is useless without flag {}", arg, needed),
+            Self::BadTheme(reason) => write!(f, "Cannot load theme: {}", reason),
+            Self::BadReference(arg, path, e) => write!(f, "Cannot read reference file {:?} for flag {}: {}", path, arg, e),
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ParseError(e) => Some(e),
+            Self::BadReference(_, _, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A list of allowed values for a flag, displayed as `choices: a, b, c`.
+struct Choices(Values);
+
+impl fmt::Display for Choices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "choices: {}", self.0.join(", "))
+    }
+}
+
+/// The maximum edit distance for an argument to be suggested.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Find the long argument of `ALL_ARGS` closest to an unknown one.
+fn suggest_long(unknown: &str) -> Option<&'static Arg> {
+    flags::ALL_ARGS.0.iter()
+        .map(|arg| (edit_distance(unknown, arg.long), *arg))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE && *distance < unknown.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, arg)| arg)
+}
+
+/// Find a short argument of `ALL_ARGS` that only differs by its case.
+fn suggest_short(unknown: ShortArg) -> Option<&'static Arg> {
+    flags::ALL_ARGS.0.iter()
+        .find(|arg| matches!(arg.short, Some(short) if short.eq_ignore_ascii_case(&unknown)))
+        .copied()
+}
+
+/// The optimal string alignment distance between two strings: the number of
+/// insertions, deletions, substitutions and adjacent transpositions needed
+/// to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod errors_test {
+    use super::*;
+
+    #[test]
+    fn distance_identical() {
+        assert_eq!(edit_distance("name", "name"), 0);
+    }
+
+    #[test]
+    fn distance_transposition() {
+        assert_eq!(edit_distance("nmae", "name"), 1);
+    }
+
+    #[test]
+    fn distance_insertion_and_deletion() {
+        assert_eq!(edit_distance("sze", "size"), 1);
+        assert_eq!(edit_distance("sizes", "size"), 1);
+    }
+
+    #[test]
+    fn suggests_close_long_argument() {
+        let error = ParseError::UnknownArgument { arg: OsString::from("nmae") };
+        assert_eq!(error.to_string(), "Unknown argument --nmae, did you mean --name?");
+    }
+
+    #[test]
+    fn no_suggestion_for_distant_argument() {
+        let error = ParseError::UnknownArgument { arg: OsString::from("whatever") };
+        assert_eq!(error.to_string(), "Unknown argument --whatever");
+    }
+
+    #[test]
+    fn suggests_short_argument_case() {
+        let error = ParseError::UnknownShortArgument { short: b'N' };
+        assert_eq!(error.to_string(), "Unknown argument -N, did you mean -n?");
+    }
+
+    #[test]
+    fn needs_value_lists_values() {
+        let error = ParseError::NeedsValue { flag: Flag::Long("completions"), values: Some(&["bash", "zsh"]) };
+        assert_eq!(error.to_string(), "Flag --completions needs a value (choices: bash, zsh)");
+    }
+}
@@ -0,0 +1,109 @@
+use crate::options::{errors::OptionsError, flags, parser::MatchedFlags, vars::{self, Vars}};
+use std::path::PathBuf;
+
+use crate::output::file_path::{Options as FileStyle, PathMode};
+use crate::output::hyperlink::ShowHyperlinks;
+use crate::output::quoting::QuotingStyle;
+
+impl FileStyle {
+    /// Deduce how the paths are printed from the given matches flags. The
+    /// hyperlinks and the current directory depend on where the search
+    /// runs, so they are added once it's known.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let quoting = QuotingStyle::deduce(matches)?;
+        let path_mode = PathMode::deduce(matches)?;
+
+        Ok(Self { quoting, hyperlinks: None, path_mode, current_dir: PathBuf::new() })
+    }
+}
+
+impl PathMode {
+    /// Deduce how the paths are printed from the given matches flags. The
+    /// last of the flags wins.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let flag = matches.has_where(|flag| {
+            flag.matches(&flags::ABSOLUTE_PATH) || flag.matches(&flags::RELATIVE_TO) || flag.matches(&flags::STRIP_CWD_PREFIX)
+        })?;
+
+        match flag {
+            Some(flag) if flag.matches(&flags::ABSOLUTE_PATH) => Ok(Self::Absolute),
+            Some(flag) if flag.matches(&flags::STRIP_CWD_PREFIX) => Ok(Self::StripCwdPrefix),
+            Some(_) => match matches.get(&flags::RELATIVE_TO)? {
+                Some(dir) => Ok(Self::RelativeTo(PathBuf::from(dir))),
+                None => Ok(Self::AsFound),
+            },
+            None => Ok(Self::AsFound),
+        }
+    }
+}
+
+impl ShowHyperlinks {
+    /// Deduce when to link the paths from the `--hyperlink` flag. Without
+    /// it, or with `--hyperlink=auto`, the paths are only linked when the
+    /// terminal is known to support hyperlinks.
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Self, OptionsError> {
+        match matches.get(&flags::HYPERLINK)? {
+            Some(word) if word == "always" => return Ok(Self::Always),
+            Some(word) if word == "never" => return Ok(Self::Never),
+            Some(word) if word == "auto" => (),
+            Some(word) => return Err(OptionsError::BadArgument(&flags::HYPERLINK, word.into())),
+            None => (),
+        }
+
+        if terminal_supports_hyperlinks(vars) {
+            Ok(Self::Automatic)
+        } else {
+            Ok(Self::Never)
+        }
+    }
+}
+
+/// Guess whether the terminal supports OSC 8 hyperlinks from the variables
+/// the terminals known to support them set.
+fn terminal_supports_hyperlinks<V: Vars>(vars: &V) -> bool {
+    let term = vars.get(vars::TERM).and_then(|v| v.into_string().ok()).unwrap_or_default();
+    if term == "dumb" {
+        return false;
+    }
+
+    if vars.get(vars::KITTY_WINDOW_ID).is_some() || vars.get(vars::WT_SESSION).is_some() || term == "xterm-kitty" {
+        return true;
+    }
+
+    let term_program = vars.get(vars::TERM_PROGRAM).and_then(|v| v.into_string().ok());
+    if matches!(term_program.as_deref(), Some("vscode" | "WezTerm" | "iTerm.app" | "ghostty")) {
+        return true;
+    }
+
+    // VTE based terminals, such as GNOME Terminal, support them since 0.50.
+    vars.get(vars::VTE_VERSION)
+        .and_then(|v| v.into_string().ok())
+        .and_then(|v| v.parse::<u32>().ok())
+        .map_or(false, |version| version >= 5000)
+}
+
+impl QuotingStyle {
+    /// Deduce the quoting style from the given matches flags. `--literal`
+    /// is a shorthand for `--quoting-style literal`, and the last of the
+    /// two wins.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let flag = matches.has_where(|flag| flag.matches(&flags::QUOTING_STYLE) || flag.matches(&flags::LITERAL))?;
+
+        match flag {
+            Some(flag) if flag.matches(&flags::LITERAL) => return Ok(Self::Literal),
+            Some(_) => (),
+            None => return Ok(Self::default()),
+        }
+
+        match matches.get(&flags::QUOTING_STYLE)? {
+            Some(word) if word == "escape" => Ok(Self::Escape),
+            Some(word) if word == "literal" => Ok(Self::Literal),
+            Some(word) if word == "shell" => Ok(Self::Shell),
+            Some(word) if word == "shell-always" => Ok(Self::ShellAlways),
+            Some(word) if word == "shell-escape" => Ok(Self::ShellEscape),
+            Some(word) if word == "c" => Ok(Self::C),
+            Some(word) => Err(OptionsError::BadArgument(&flags::QUOTING_STYLE, word.into())),
+            None => Ok(Self::default()),
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use std::os::unix::prelude::OsStrExt;
 
-use crate::fs::filter::{FileFilter, SizeFilter, NameFilter};
-use crate::options::{parser::MatchedFlags, errors::OptionsError, flags};
+use std::time::SystemTime;
+
+use regex::bytes::Regex;
+
+use crate::fs::filter::{DateFilter, TimeField, FileFilter, SizeFilter, NameFilter, LinkFilter, ExecutableFilter, ExtensionFilter, EmptyFilter, PermFilter, AccessFilter, OwnerFilter};
+use crate::fs::users::UsersCache;
+use crate::options::{parser::{Arg, MatchedFlags}, errors::OptionsError, flags};
 
 impl FileFilter {
-    /// Deduce a FileFilter from the given matches flags.
+    /// Deduce a FileFilter from the given matches flags. The empty filters
+    /// match directories too, so they include them.
     pub fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
         let only_dirs = matches.has(&flags::ONLY_DIRS)?;
         let include_dirs = matches.has(&flags::INCLUDE_DIRS)?;
@@ -14,12 +20,21 @@ impl FileFilter {
             return Err(OptionsError::OptionsConflit(&flags::ONLY_DIRS, &flags::INCLUDE_DIRS))
         }
 
+        let empty_filter = EmptyFilter::deduce(matches)?;
+
         Ok(Self {
             only_dirs,
-            include_dirs,
+            include_dirs: include_dirs || empty_filter != EmptyFilter::Unfiltered,
             size_filter: SizeFilter::deduce(matches)?,
             name_filter: NameFilter::deduce(matches)?,
-            date_filter: None
+            extension_filter: ExtensionFilter::deduce(matches)?,
+            executable_filter: ExecutableFilter::deduce(matches)?,
+            empty_filter,
+            perm_filter: PermFilter::deduce(matches)?,
+            access_filter: AccessFilter::deduce(matches)?,
+            owner_filter: OwnerFilter::deduce(matches)?,
+            link_filter: LinkFilter::deduce(matches)?,
+            date_filter: DateFilter::deduce(matches)?
         })
     }
 }
@@ -28,27 +43,189 @@ impl FileFilter {
 impl NameFilter {
     /// Deduce a FileNameFilter from the given matches flags.
     fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
-        let name_os_str = matches.get(&flags::NAME)?;
-        let reggex = match name_os_str {
-            Some(os_str) => {
-                match os_str.to_str() {
-                    Some(w) => {
-                        match regex::Regex::new(w) {
-                            Ok(r) => Some(r),
-                            Err(_) => return Err(OptionsError::BadArgument(&flags::NAME, os_str.into())),
-                        }
-                    },
-                    None => return Err(OptionsError::BadArgument(&flags::NAME, os_str.into()))
-                }
-            },
-            None => None,
-        };
+        let reggex = deduce_regex(matches, &flags::NAME)?;
 
-        
         Ok(Self::from(reggex))
     }
 }
 
+impl ExtensionFilter {
+    /// Deduce an ExtensionFilter from the given matches flags. The flag can
+    /// be repeated, and each value is a comma-separated list.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let mut filter = Self::default();
+
+        for os_str in matches.get_all(&flags::EXTENSION) {
+            match os_str.to_str() {
+                Some(list) => filter.add_list(list),
+                None => return Err(OptionsError::BadArgument(&flags::EXTENSION, os_str.into())),
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+impl ExecutableFilter {
+    /// Deduce an ExecutableFilter from the given matches flags.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let executable = matches.has(&flags::EXECUTABLE)?;
+        let not_executable = matches.has(&flags::NOT_EXECUTABLE)?;
+
+        match (executable, not_executable) {
+            (true, true) => Err(OptionsError::OptionsConflit(&flags::EXECUTABLE, &flags::NOT_EXECUTABLE)),
+            (true, false) => Ok(Self::Executable),
+            (false, true) => Ok(Self::NotExecutable),
+            (false, false) => Ok(Self::Unfiltered),
+        }
+    }
+}
+
+impl DateFilter {
+    /// Deduce a DateFilter from the given matches flags. The reference
+    /// files are read once, here, and their timestamps reused for every
+    /// file of the search.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<Self>, OptionsError> {
+        let time = TimeField::deduce(matches)?;
+        let newer_than = deduce_reference_time(matches, &flags::NEWER, time)?;
+        let older_than = deduce_reference_time(matches, &flags::OLDER, time)?;
+
+        if newer_than.is_none() && older_than.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { time, newer_than, older_than }))
+    }
+}
+
+impl TimeField {
+    /// Deduce a TimeField from the given matches flags. The letters of
+    /// find's `-newerXY` are accepted too, and `birth` like `created`.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let word = match matches.get(&flags::TIME)? {
+            Some(word) => word,
+            None => return Ok(Self::default()),
+        };
+
+        match word.to_str() {
+            Some("modified" | "m") => Ok(Self::Modified),
+            Some("accessed" | "a") => Ok(Self::Accessed),
+            Some("changed"  | "c") => Ok(Self::Changed),
+            Some("created"  | "birth" | "b") => Ok(Self::Created),
+            _ => Err(OptionsError::BadArgument(&flags::TIME, word.into())),
+        }
+    }
+}
+
+/// Read the given timestamp of the reference file of a flag. Like the
+/// files of the search, a reference file that is a symlink is not followed,
+/// so its own timestamp is compared.
+fn deduce_reference_time(matches: &MatchedFlags, arg: &'static Arg, time: TimeField) -> Result<Option<SystemTime>, OptionsError> {
+    let path = match matches.get(arg)? {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    std::fs::symlink_metadata(path)
+        .and_then(|metadata| time.of(&metadata))
+        .map(Some)
+        .map_err(|e| OptionsError::BadReference(arg, path.into(), e))
+}
+
+impl EmptyFilter {
+    /// Deduce an EmptyFilter from the given matches flags.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        if matches.has(&flags::EMPTY_RECURSIVE)? {
+            Ok(Self::EmptyRecursive)
+        } else if matches.has(&flags::EMPTY)? {
+            Ok(Self::Empty)
+        } else {
+            Ok(Self::Unfiltered)
+        }
+    }
+}
+
+impl PermFilter {
+    /// Deduce a PermFilter from the given matches flags.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let os_str = match matches.get(&flags::PERM)? {
+            Some(os_str) => os_str,
+            None => return Ok(Self::Unfiltered),
+        };
+
+        os_str.to_str()
+            .and_then(Self::parse)
+            .ok_or_else(|| OptionsError::BadArgument(&flags::PERM, os_str.into()))
+    }
+}
+
+impl AccessFilter {
+    /// Deduce an AccessFilter from the given matches flags.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        Ok(Self {
+            readable: matches.has(&flags::READABLE)?,
+            writable: matches.has(&flags::WRITABLE)?,
+            executable: matches.has(&flags::EXECUTABLE_BY_ME)?,
+        })
+    }
+}
+
+impl OwnerFilter {
+    /// Deduce an OwnerFilter from the given matches flags. Users and groups
+    /// are given either by name or by numeric id. Like find, a name wins
+    /// over an id, so a user named `1000` is found by its name.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let users = UsersCache::default();
+
+        let uid = deduce_id(matches, &flags::OWNER, |name| users.uid_by_name(name))?;
+        let gid = deduce_id(matches, &flags::GROUP, |name| users.gid_by_name(name))?;
+        let no_user = matches.has(&flags::NOUSER)?;
+        let no_group = matches.has(&flags::NOGROUP)?;
+
+        Ok(Self { uid, gid, no_user, no_group, users })
+    }
+}
+
+/// Resolve the value of a flag into a user or group id.
+fn deduce_id<F>(matches: &MatchedFlags, arg: &'static Arg, by_name: F) -> Result<Option<u32>, OptionsError>
+where F: Fn(&str) -> Option<u32>
+{
+    let os_str = match matches.get(arg)? {
+        Some(os_str) => os_str,
+        None => return Ok(None),
+    };
+
+    let id = os_str.to_str().and_then(|value| by_name(value).or_else(|| value.parse().ok()));
+
+    match id {
+        Some(id) => Ok(Some(id)),
+        None => Err(OptionsError::BadArgument(arg, os_str.into())),
+    }
+}
+
+impl LinkFilter {
+    /// Deduce a LinkFilter from the given matches flags.
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let broken = matches.has(&flags::BROKEN_LINKS)?;
+        let target = deduce_regex(matches, &flags::LINK_TARGET)?;
+
+        Ok(Self { broken, target })
+    }
+}
+
+/// Compile the value of a flag into a regular expression.
+fn deduce_regex(matches: &MatchedFlags, arg: &'static Arg) -> Result<Option<Regex>, OptionsError> {
+    let os_str = match matches.get(arg)? {
+        Some(os_str) => os_str,
+        None => return Ok(None),
+    };
+
+    match os_str.to_str().map(Regex::new) {
+        Some(Ok(regex)) => Ok(Some(regex)),
+        _ => Err(OptionsError::BadArgument(arg, os_str.into())),
+    }
+}
+
 impl SizeFilter {
     /// Deduce a FileSizeFilter from the given matches flags.
     fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
@@ -64,4 +241,47 @@ impl SizeFilter {
 
         Ok(size_filter)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod date_filter_test {
+    use super::*;
+    use crate::options::test::parse_for_test;
+
+    #[test]
+    fn time_fields() {
+        assert_eq!(TimeField::deduce(&parse_for_test(&[])).unwrap(), TimeField::Modified);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "modified"])).unwrap(), TimeField::Modified);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "accessed"])).unwrap(), TimeField::Accessed);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "changed"])).unwrap(), TimeField::Changed);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "created"])).unwrap(), TimeField::Created);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "created", "--time", "accessed"])).unwrap(), TimeField::Accessed);
+    }
+
+    #[test]
+    fn time_field_aliases() {
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "m"])).unwrap(), TimeField::Modified);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "a"])).unwrap(), TimeField::Accessed);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "c"])).unwrap(), TimeField::Changed);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "b"])).unwrap(), TimeField::Created);
+        assert_eq!(TimeField::deduce(&parse_for_test(&["--time", "birth"])).unwrap(), TimeField::Created);
+    }
+
+    #[test]
+    fn unlisted_time_fields() {
+        for word in ["Modified", "M", "mtime", ""] {
+            let error = TimeField::deduce(&parse_for_test(&["--time", word])).err().unwrap();
+            assert!(matches!(error, OptionsError::BadArgument(arg, _) if arg == &flags::TIME), "{}", word);
+        }
+    }
+
+    #[test]
+    fn no_reference() {
+        assert!(DateFilter::deduce(&parse_for_test(&["--time", "accessed"])).unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_reference() {
+        let error = DateFilter::deduce(&parse_for_test(&["--newer", "/search-test-missing-reference"])).err().unwrap();
+        assert!(matches!(error, OptionsError::BadReference(arg, _, _) if arg == &flags::NEWER));
+    }
+}
@@ -1,18 +1,76 @@
-use crate::options::parser::{Arg, TakesValue, Args};
+use crate::options::parser::{Arg, TakesValue, Args, Section};
 
 // search options
-pub static VERSION: Arg = Arg { short: Some(b'v'), long: "version", takes_value: TakesValue::Forbidden };
-pub static HELP: Arg = Arg { short: Some(b'h'), long: "help", takes_value: TakesValue::Forbidden };
+pub static VERSION: Arg = Arg { short: Some(b'v'), long: "version", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Meta, description: "show the version of search" };
+pub static VERBOSE: Arg = Arg { short: None, long: "verbose", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Meta, description: "with --version, print machine-readable build information" };
+pub static HELP: Arg = Arg { short: Some(b'h'), long: "help", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Meta, description: "show this help (-h for a summary)" };
+pub static MAN: Arg = Arg { short: None, long: "man", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Meta, description: "print the manual page in roff format" };
+pub static COMPLETIONS: Arg = Arg { short: None, long: "completions", takes_value: TakesValue::Necessary(Some(SHELLS)), value_name: Some("SHELL"), section: Section::Meta, description: "print the completion script for SHELL" };
+const SHELLS: &[&str] = &["bash", "zsh", "fish"];
 
 // filtering options
-pub static NAME: Arg = Arg { short: Some(b'n'), long: "name", takes_value: TakesValue::Necessary(None) };
-pub static INCLUDE_DIRS: Arg = Arg { short: None, long: "include-dirs", takes_value: TakesValue::Forbidden };
-pub static ONLY_DIRS: Arg = Arg { short: Some(b'd'), long: "only-dirs", takes_value: TakesValue::Forbidden };
-pub static SIZE: Arg = Arg { short: Some(b's'), long: "size", takes_value: TakesValue::Necessary(None) };
+pub static NAME: Arg = Arg { short: Some(b'n'), long: "name", takes_value: TakesValue::Necessary(None), value_name: Some("REGEX"), section: Section::Filtering, description: "filter the files by name with a regular expression" };
+pub static INCLUDE_DIRS: Arg = Arg { short: None, long: "include-dirs", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "include the directories in the search" };
+pub static ONLY_DIRS: Arg = Arg { short: Some(b'd'), long: "only-dirs", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the directories" };
+pub static SIZE: Arg = Arg { short: Some(b's'), long: "size", takes_value: TakesValue::Necessary(None), value_name: Some("SIZE"), section: Section::Filtering, description: "filter the files by size (e.g. +10k, -=2M)" };
+pub static EXTENSION: Arg = Arg { short: Some(b'e'), long: "extension", takes_value: TakesValue::Necessary(None), value_name: Some("EXTS"), section: Section::Filtering, description: "only match the files with one of the comma-separated extensions (rs,tar.gz), an empty one for no extension; repeatable" };
+pub static EXECUTABLE: Arg = Arg { short: None, long: "executable", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the regular files with an executable bit set" };
+pub static NOT_EXECUTABLE: Arg = Arg { short: None, long: "not-executable", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the regular files without any executable bit set" };
+pub static NEWER: Arg = Arg { short: None, long: "newer", takes_value: TakesValue::Necessary(None), value_name: Some("FILE"), section: Section::Filtering, description: "only match the files more recent than FILE" };
+pub static OLDER: Arg = Arg { short: None, long: "older", takes_value: TakesValue::Necessary(None), value_name: Some("FILE"), section: Section::Filtering, description: "only match the files older than FILE" };
+pub static TIME: Arg = Arg { short: None, long: "time", takes_value: TakesValue::Necessary(Some(TIMES)), value_name: Some("FIELD"), section: Section::Filtering, description: "the timestamp compared by --newer and --older" };
+const TIMES: &[&str] = &["modified", "accessed", "changed", "created", "m", "a", "c", "b", "birth"];
+pub static EMPTY: Arg = Arg { short: None, long: "empty", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the empty regular files and directories" };
+pub static EMPTY_RECURSIVE: Arg = Arg { short: None, long: "empty-recursive", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "like --empty, but directories holding only empty directories are empty too" };
+pub static PERM: Arg = Arg { short: None, long: "perm", takes_value: TakesValue::Necessary(None), value_name: Some("MODE"), section: Section::Filtering, description: "filter by mode, octal (644) or symbolic (u+x,g-w): exactly MODE, -MODE for all of its bits, /MODE for any of them" };
+pub static READABLE: Arg = Arg { short: None, long: "readable", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the files the current user can read" };
+pub static WRITABLE: Arg = Arg { short: None, long: "writable", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the files the current user can write" };
+pub static EXECUTABLE_BY_ME: Arg = Arg { short: None, long: "executable-by-me", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the files the current user can execute" };
+pub static OWNER: Arg = Arg { short: None, long: "owner", takes_value: TakesValue::Necessary(None), value_name: Some("USER"), section: Section::Filtering, description: "only match the files owned by USER, a name or a numeric id" };
+pub static GROUP: Arg = Arg { short: None, long: "group", takes_value: TakesValue::Necessary(None), value_name: Some("GROUP"), section: Section::Filtering, description: "only match the files owned by GROUP, a name or a numeric id" };
+pub static NOUSER: Arg = Arg { short: None, long: "nouser", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the files whose user id has no entry in /etc/passwd" };
+pub static NOGROUP: Arg = Arg { short: None, long: "nogroup", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the files whose group id has no entry in /etc/group" };
+pub static BROKEN_LINKS: Arg = Arg { short: None, long: "broken-links", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Filtering, description: "only match the symlinks whose target doesn't exist" };
+pub static LINK_TARGET: Arg = Arg { short: None, long: "link-target", takes_value: TakesValue::Necessary(None), value_name: Some("REGEX"), section: Section::Filtering, description: "only match the symlinks whose target path matches REGEX" };
+
+// traversal options
+pub static SKIP_DENIED: Arg = Arg { short: None, long: "skip-denied", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Traversal, description: "silently skip the paths that can't be read for lack of permission, without changing the exit code" };
+
+pub static QUIET_ERRORS: Arg = Arg { short: None, long: "quiet-errors", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Traversal, description: "don't print the errors met during the search; they still change the exit code" };
+pub static ERROR_FORMAT: Arg = Arg { short: None, long: "error-format", takes_value: TakesValue::Necessary(Some(ERROR_FORMATS)), value_name: Some("FORMAT"), section: Section::Traversal, description: "the format of the errors printed to the standard error" };
+const ERROR_FORMATS: &[&str] = &["text", "json"];
+pub static BASE_DIRECTORY: Arg = Arg { short: None, long: "base-directory", takes_value: TakesValue::Necessary(None), value_name: Some("DIR"), section: Section::Traversal, description: "run the search from DIR: the paths to search are resolved from it, and printed as found from it, while the paths given to the other options are resolved from the current directory" };
+pub static FILES_FROM: Arg = Arg { short: None, long: "files-from", takes_value: TakesValue::Necessary(None), value_name: Some("PATH"), section: Section::Traversal, description: "filter the paths listed in PATH, one per line, without walking them; - reads the standard input" };
+
+// display options
+pub static THEME: Arg = Arg { short: None, long: "theme", takes_value: TakesValue::Necessary(None), value_name: Some("NAME"), section: Section::Display, description: "use the theme NAME: a preset (default, light, dark, high-contrast), a theme file or a path" };
+
+pub static COLOR: Arg = Arg { short: None, long: "color", takes_value: TakesValue::Necessary(Some(COLOURS)), value_name: Some("WHEN"), section: Section::Display, description: "when to use terminal colours" };
+const COLOURS: &[&str] = &["auto", "always", "never"];
+pub static HYPERLINK: Arg = Arg { short: None, long: "hyperlink", takes_value: TakesValue::Necessary(Some(HYPERLINKS)), value_name: Some("WHEN"), section: Section::Display, description: "when to make the paths terminal hyperlinks to the files" };
+const HYPERLINKS: &[&str] = &["auto", "always", "never"];
+pub static LITERAL: Arg = Arg { short: None, long: "literal", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Display, description: "print the paths as raw bytes, like --quoting-style literal" };
+pub static QUOTING_STYLE: Arg = Arg { short: None, long: "quoting-style", takes_value: TakesValue::Necessary(Some(QUOTING_STYLES)), value_name: Some("STYLE"), section: Section::Display, description: "how the paths are quoted: escape (the default) only escapes control characters and invalid UTF-8, the shell styles can be pasted in a shell" };
+const QUOTING_STYLES: &[&str] = &["escape", "literal", "shell", "shell-always", "shell-escape", "c"];
+pub static ABSOLUTE_PATH: Arg = Arg { short: None, long: "absolute-path", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Display, description: "print the paths as absolute paths" };
+pub static RELATIVE_TO: Arg = Arg { short: None, long: "relative-to", takes_value: TakesValue::Necessary(None), value_name: Some("DIR"), section: Section::Display, description: "print the paths relative to DIR" };
+pub static STRIP_CWD_PREFIX: Arg = Arg { short: None, long: "strip-cwd-prefix", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Display, description: "print the paths found from the current directory without their ./ prefix" };
+pub static MAX_RESULTS: Arg = Arg { short: None, long: "max-results", takes_value: TakesValue::Necessary(None), value_name: Some("N"), section: Section::Limits, description: "stop the search once N files have been printed" };
+pub static FIRST: Arg = Arg { short: Some(b'1'), long: "first", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Limits, description: "stop the search at the first match, like --max-results 1" };
+pub static QUIET: Arg = Arg { short: Some(b'q'), long: "quiet", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Limits, description: "print nothing, exit with 0 at the first match or 1 without any" };
 
 // All args
 pub static ALL_ARGS: Args = Args(&[
-    &VERSION, &HELP,
+    &VERSION, &VERBOSE, &HELP, &MAN, &COMPLETIONS,
+
+    &NAME, &INCLUDE_DIRS, &ONLY_DIRS, &SIZE, &EXTENSION, &EXECUTABLE, &NOT_EXECUTABLE,
+    &NEWER, &OLDER, &TIME, &EMPTY, &EMPTY_RECURSIVE, &PERM, &READABLE, &WRITABLE, &EXECUTABLE_BY_ME,
+    &OWNER, &GROUP, &NOUSER, &NOGROUP, &BROKEN_LINKS, &LINK_TARGET,
+
+    &SKIP_DENIED, &QUIET_ERRORS, &ERROR_FORMAT, &FILES_FROM, &BASE_DIRECTORY,
+
+    &THEME, &COLOR, &HYPERLINK, &LITERAL, &QUOTING_STYLE,
+    &ABSOLUTE_PATH, &RELATIVE_TO, &STRIP_CWD_PREFIX,
 
-    &NAME, &INCLUDE_DIRS, &ONLY_DIRS, &SIZE,
-]);
\ No newline at end of file
+    &MAX_RESULTS, &FIRST, &QUIET,
+]);
@@ -1,35 +1,77 @@
 use std::fmt;
 
-use crate::options::parser::MatchedFlags;
+use crate::options::parser::{Arg, Flag, MatchedFlags, Section, TakesValue};
 use crate::options::flags;
 
-/// The help string.
-/// This string is printed when the user asks for help.
-static USAGE: &str = "Usage: 
-    search [options] [directories...]
-
-META OPTIONS
-    -h, --help          show this!
-    -v, --version       show the version of search
-
-FILTERING OPTIONS
-    -n, --name          filter the files by name
-    -s, --size          filter the files by size
-    --include-dirs      include the directories in the search
-    --only-dirs         only search in the directories
-";
+/// The usage line printed at the top of the help text.
+pub static USAGE: &str = "search [options] [paths...]";
 
 /// A struct that represents the help string.
-pub struct HelpString;
+/// This string is printed when the user asks for help. It is generated
+/// from the `ALL_ARGS` table so it can't drift from the parser.
+pub struct HelpString {
+    /// Whether the detailed help (`--help`) or the summary (`-h`) is printed.
+    /// The summary has the sections of the detailed help, with the names
+    /// of their flags but without their descriptions.
+    pub detailed: bool,
+}
 
 impl HelpString {
     /// Deduce a HelpString from the given matches flags.
+    /// The long flag asks for the detailed help and wins over the short one.
     pub fn deduce(matches: &MatchedFlags<'_>) -> Option<Self> {
-        if matches.count(&flags::HELP) > 0 {
-            Some(Self)
-        } else {
-            None
+        if matches.count(&flags::HELP) == 0 {
+            return None;
+        }
+
+        let detailed = matches.has_where_any(|flag| *flag == Flag::Long(flags::HELP.long)).is_some();
+        Some(Self { detailed })
+    }
+
+    fn write_summary(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for section in Section::ALL {
+            writeln!(f, "{}  {}", section.title(), section.summary())?;
+
+            let mut line = String::new();
+            for arg in flags::ALL_ARGS.0.iter().filter(|arg| arg.section == *section) {
+                let names = short_flag_names(arg);
+
+                if !line.is_empty() && SUMMARY_INDENT.len() + line.len() + 2 + names.len() > SUMMARY_WIDTH {
+                    writeln!(f, "{}{}", SUMMARY_INDENT, line)?;
+                    line.clear();
+                }
+
+                if !line.is_empty() {
+                    line.push_str("  ");
+                }
+                line.push_str(&names);
+            }
+
+            writeln!(f, "{}{}", SUMMARY_INDENT, line)?;
+            writeln!(f)?;
         }
+
+        write!(f, "Run 'search --help' for the detailed help.")
+    }
+
+    fn write_detailed(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = column_width();
+
+        for section in Section::ALL {
+            writeln!(f, "{}", section.title())?;
+
+            for arg in flags::ALL_ARGS.0.iter().filter(|arg| arg.section == *section) {
+                writeln!(f, "    {:width$}  {}", flag_names(arg), arg.description, width = width)?;
+
+                if let Some(values) = values(arg) {
+                    writeln!(f, "    {:width$}  possible values: {}", "", values.join(", "), width = width)?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        write!(f, "Run 'search --man' for the manual page.")
     }
 }
 
@@ -37,8 +79,106 @@ impl HelpString {
 /// This allows us to print the help string.
 impl fmt::Display for HelpString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", USAGE)?;
+        writeln!(f, "Usage:")?;
+        writeln!(f, "    {}", USAGE)?;
+        writeln!(f)?;
+
+        if self.detailed {
+            Self::write_detailed(f)?;
+        } else {
+            Self::write_summary(f)?;
+        }
 
         writeln!(f)
     }
-}
\ No newline at end of file
+}
+
+/// The names of a flag with its value name, as displayed in the help text.
+///
+///   -n, --name REGEX
+///       --include-dirs
+pub fn flag_names(arg: &Arg) -> String {
+    let mut names = match arg.short {
+        Some(short) => format!("-{}, --{}", short as char, arg.long),
+        None => format!("    --{}", arg.long),
+    };
+
+    if let Some(value_name) = arg.value_name {
+        names.push(' ');
+        names.push_str(value_name);
+    }
+
+    names
+}
+
+/// The indentation of the flags in the summary.
+static SUMMARY_INDENT: &str = "    ";
+
+/// The width the flags of the summary are wrapped at.
+const SUMMARY_WIDTH: usize = 80;
+
+/// The names of a flag with its value name, as listed in the summary.
+///
+///   -n/--name REGEX
+///   --include-dirs
+fn short_flag_names(arg: &Arg) -> String {
+    let mut names = match arg.short {
+        Some(short) => format!("-{}/--{}", short as char, arg.long),
+        None => format!("--{}", arg.long),
+    };
+
+    if let Some(value_name) = arg.value_name {
+        names.push(' ');
+        names.push_str(value_name);
+    }
+
+    names
+}
+
+/// The list of allowed values of a flag, if it has one.
+pub fn values(arg: &Arg) -> Option<&'static [&'static str]> {
+    match arg.takes_value {
+        TakesValue::Necessary(values) |
+        TakesValue::Optional(values) => values,
+        TakesValue::Forbidden => None,
+    }
+}
+
+fn column_width() -> usize {
+    flags::ALL_ARGS.0.iter()
+        .map(|arg| flag_names(arg).len())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod help_test {
+    use super::*;
+
+    #[test]
+    fn summary_lists_every_flag() {
+        let help = HelpString { detailed: false }.to_string();
+        for section in Section::ALL {
+            assert!(help.contains(&format!("{}  {}\n", section.title(), section.summary())));
+        }
+        for arg in flags::ALL_ARGS.0 {
+            assert!(help.contains(&short_flag_names(arg)), "--{} isn’t listed", arg.long);
+            assert!(!help.contains(arg.description), "--{} is described", arg.long);
+        }
+    }
+
+    #[test]
+    fn summary_is_wrapped() {
+        let help = HelpString { detailed: false }.to_string();
+        assert!(help.lines().all(|line| line.len() <= SUMMARY_WIDTH || !line.starts_with(SUMMARY_INDENT)));
+        assert!(help.contains("    -n/--name REGEX  --include-dirs"));
+    }
+
+    #[test]
+    fn detailed_has_every_flag() {
+        let help = HelpString { detailed: true }.to_string();
+        for arg in flags::ALL_ARGS.0 {
+            assert!(help.contains(&flag_names(arg)), "--{} isn’t documented", arg.long);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::options::{errors::OptionsError, flags, parser::MatchedFlags};
+use crate::output::limits::Limits;
+
+impl Limits {
+    /// Deduce the limits from the given matches flags. `-1` is a shorthand
+    /// for `--max-results 1`, and the last of the two wins.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let quiet = matches.has(&flags::QUIET)?;
+
+        let max_results = match matches.has_where(|flag| flag.matches(&flags::MAX_RESULTS) || flag.matches(&flags::FIRST))? {
+            Some(flag) if flag.matches(&flags::FIRST) => Some(1),
+            Some(_) => {
+                let os_str = matches.get(&flags::MAX_RESULTS)?.unwrap_or_default();
+
+                match os_str.to_str().and_then(|s| s.parse::<usize>().ok()) {
+                    Some(n) if n > 0 => Some(n),
+                    _ => return Err(OptionsError::BadArgument(&flags::MAX_RESULTS, os_str.into())),
+                }
+            },
+            None => None,
+        };
+
+        Ok(Self { max_results, quiet })
+    }
+}
+
+#[cfg(test)]
+mod limits_test {
+    use super::*;
+    use crate::options::test::parse_for_test;
+
+    fn deduce(args: &[&'static str]) -> Result<Limits, OptionsError> {
+        Limits::deduce(&parse_for_test(args))
+    }
+
+    #[test]
+    fn no_limits() {
+        assert_eq!(deduce(&[]).unwrap(), Limits { max_results: None, quiet: false });
+        assert_eq!(deduce(&[]).unwrap().stop_after(), None);
+    }
+
+    #[test]
+    fn max_results() {
+        assert_eq!(deduce(&["--max-results", "3"]).unwrap().max_results, Some(3));
+        assert_eq!(deduce(&["--max-results", "3", "--max-results", "5"]).unwrap().max_results, Some(5));
+    }
+
+    #[test]
+    fn first_and_max_results_last_wins() {
+        assert_eq!(deduce(&["-1", "--max-results", "3"]).unwrap().max_results, Some(3));
+        assert_eq!(deduce(&["--max-results", "3", "-1"]).unwrap().max_results, Some(1));
+        assert_eq!(deduce(&["--max-results", "3", "--first"]).unwrap().max_results, Some(1));
+    }
+
+    #[test]
+    fn bad_max_results() {
+        for value in ["0", "-2", "three", "1.5", ""] {
+            let error = deduce(&["--max-results", value]).err().unwrap();
+            assert!(matches!(error, OptionsError::BadArgument(arg, _) if arg == &flags::MAX_RESULTS), "{}", value);
+        }
+    }
+
+    #[test]
+    fn quiet_stops_at_the_first_match() {
+        let limits = deduce(&["--quiet", "--max-results", "3"]).unwrap();
+        assert!(limits.quiet);
+        assert_eq!(limits.stop_after(), Some(1));
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+
+use crate::exits;
+use crate::options::flags;
+use crate::options::help::{flag_names, values, USAGE};
+use crate::options::parser::{MatchedFlags, Section};
+
+/// A struct that represents the manual page.
+/// The page is written in roff and generated from the same metadata as
+/// the help text.
+pub struct ManPage;
+
+impl ManPage {
+    /// Deduce a ManPage from the given matches flags.
+    pub fn deduce(matches: &MatchedFlags<'_>) -> Option<Self> {
+        if matches.count(&flags::MAN) > 0 {
+            Some(Self)
+        } else {
+            None
+        }
+    }
+}
+
+/// Implement the Display trait for ManPage.
+/// This allows us to print the manual page.
+impl fmt::Display for ManPage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, ".TH SEARCH 1 \"{}\" \"search {}\" \"User Commands\"", env!("SEARCH_BUILD_DATE"), env!("CARGO_PKG_VERSION"))?;
+        writeln!(f, ".SH NAME")?;
+        writeln!(f, "search \\- find the files matching a set of filters")?;
+        writeln!(f, ".SH SYNOPSIS")?;
+        writeln!(f, "{}", escape(USAGE))?;
+        writeln!(f, ".SH DESCRIPTION")?;
+        writeln!(f, "Walks the given directories, or the current one when no path is given,")?;
+        writeln!(f, "and prints the path of every file matching the filters. The files given")?;
+        writeln!(f, "as paths, and the paths listed by \\-\\-files\\-from, are filtered without being walked.")?;
+
+        for section in Section::ALL {
+            writeln!(f, ".SH {}", section.title())?;
+
+            for arg in flags::ALL_ARGS.0.iter().filter(|arg| arg.section == *section) {
+                writeln!(f, ".TP")?;
+                writeln!(f, ".B {}", escape(flag_names(arg).trim_start()))?;
+                writeln!(f, "{}", escape(arg.description))?;
+
+                if let Some(values) = values(arg) {
+                    writeln!(f, ".br")?;
+                    writeln!(f, "Possible values: {}", escape(&values.join(", ")))?;
+                }
+            }
+        }
+
+        writeln!(f, ".SH EXIT STATUS")?;
+        for (code, meaning) in EXIT_STATUSES {
+            writeln!(f, ".TP")?;
+            writeln!(f, ".B {}", code)?;
+            writeln!(f, "{}", escape(meaning))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The exit codes of search, and what they mean.
+static EXIT_STATUSES: &[(i32, &str)] = &[
+    (exits::SUCCESS,     "At least one file matched."),
+    (exits::NO_MATCHES,  "No file matched."),
+    (exits::IO_ERROR,    "Some paths couldn't be read, so the results may be incomplete. With --quiet, a match exits with 0 anyway."),
+    (exits::FATAL_ERROR, "The options are invalid, or the output couldn't be written."),
+];
+
+/// Escape the characters roff would otherwise interpret. A line starting
+/// with `.` or `'` would be read as a request, so it gets a zero-width
+/// `\&` first.
+fn escape(input: &str) -> String {
+    let escaped = input.replace('\\', "\\e").replace('-', "\\-");
+
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod man_test {
+    use super::*;
+
+    #[test]
+    fn escapes() {
+        assert_eq!(escape("a-b\\c"), "a\\-b\\ec");
+        assert_eq!(escape(".hidden files"), "\\&.hidden files");
+        assert_eq!(escape("'quoted'"), "\\&'quoted'");
+    }
+
+    #[test]
+    fn title_has_a_date() {
+        let page = ManPage.to_string();
+        let title = page.lines().next().unwrap();
+        assert!(!title.contains("\"\""), "{}", title);
+    }
+}
@@ -1,6 +1,13 @@
 use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
+use crate::fs::files_from::FilesFrom;
 use crate::fs::filter::FileFilter;
+use crate::output::file_path::{Options as FileStyle, PathMode};
+use crate::output::hyperlink::ShowHyperlinks;
+use crate::output::limits::Limits;
+use crate::output::report::Report;
+use crate::theme::Options as ThemeOptions;
 
 mod parser;
 use crate::options::parser::MatchedFlags;
@@ -13,17 +20,64 @@ mod flags;
 mod help;
 use crate::options::help::HelpString;
 
+mod completions;
+use crate::options::completions::Completions;
+
+mod man;
+use crate::options::man::ManPage;
+
+mod version;
+use crate::options::version::VersionString;
+
 mod filter;
 
+mod file_path;
+
+mod limits;
+
+mod report;
+
+mod traversal;
+
+mod theme;
+
+pub mod vars;
+pub use crate::options::vars::Vars;
+
 /// A struct that represents the options given by the user.
 pub struct Options {
     /// The filter to use to filter the files.
     pub filter: FileFilter,
+
+    /// The options to build the theme with.
+    pub theme: ThemeOptions,
+
+    /// How the paths of the matched files are printed.
+    pub file_style: FileStyle,
+
+    /// When to wrap the printed paths in hyperlinks.
+    pub hyperlinks: ShowHyperlinks,
+
+    /// How many files are printed, and when the search stops.
+    pub limits: Limits,
+
+    /// How the errors met during the search are reported.
+    pub report: Report,
+
+    /// Where to read the list of paths to search from, if anywhere.
+    pub files_from: Option<FilesFrom>,
+
+    /// The directory the search runs from, if it isn't the current one.
+    /// Only the paths to search, given as arguments or listed by
+    /// `--files-from`, are resolved from it: the paths given to the
+    /// options are resolved from the directory search was started in.
+    pub base_directory: Option<PathBuf>,
 }
 
 impl Options {
-    pub fn parse<'args, I>(args: I) -> OptionsResult<'args>
-    where I: IntoIterator<Item = &'args OsStr> 
+    pub fn parse<'args, I, V>(args: I, vars: &V) -> OptionsResult<'args>
+    where I: IntoIterator<Item = &'args OsStr>,
+          V: Vars,
     {
         use crate::options::parser::{Matches, Strictness};
 
@@ -36,23 +90,118 @@ impl Options {
             return OptionsResult::Help(help);
         }
 
-        match Self::deduce(&flags) {
+        if let Some(man) = ManPage::deduce(&flags) {
+            return OptionsResult::Man(man);
+        }
+
+        match VersionString::deduce(&flags) {
+            Ok(Some(version)) => return OptionsResult::Version(version),
+            Ok(None) => (),
+            Err(e) => return OptionsResult::InvalidOptions(e),
+        }
+
+        match Completions::deduce(&flags) {
+            Ok(Some(completions)) => return OptionsResult::Completions(completions),
+            Ok(None) => (),
+            Err(e) => return OptionsResult::InvalidOptions(e),
+        }
+
+        match Self::deduce(&flags, vars) {
             Ok(p) => OptionsResult::Ok(p, frees),
             Err(e) => OptionsResult::InvalidOptions(e),
         }
     }
 
-    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
-        let filter = FileFilter::deduce(matches)?;
+    /// Resolve the relative paths of the options that are only used once
+    /// the search has moved to its base directory from the given
+    /// directory, the one search was started in. The other option paths
+    /// are read while the options are deduced, before the move.
+    pub fn anchor_paths(&mut self, dir: &Path) {
+        if let Some(FilesFrom::File(path)) = &mut self.files_from {
+            *path = dir.join(&*path);
+        }
 
-        Ok(Self { filter })
+        if let PathMode::RelativeTo(path) = &mut self.file_style.path_mode {
+            *path = dir.join(&*path);
+        }
+    }
+
+    fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Self, OptionsError> {
+        let filter = FileFilter::deduce(matches)?;
+        let theme = ThemeOptions::deduce(matches, vars)?;
+        let file_style = FileStyle::deduce(matches)?;
+        let hyperlinks = ShowHyperlinks::deduce(matches, vars)?;
+        let limits = Limits::deduce(matches)?;
+        let report = Report::deduce(matches)?;
+        let files_from = FilesFrom::deduce(matches)?;
+        let base_directory = traversal::deduce_base_directory(matches)?;
+
+        Ok(Self { filter, theme, file_style, hyperlinks, limits, report, files_from, base_directory })
     }
 }
 
+// Only one result is ever built, so the size of the options doesn't matter.
+#[allow(clippy::large_enum_variant)]
 pub enum OptionsResult<'args> {
     Ok(Options, Vec<&'args OsStr>),
 
     InvalidOptions(OptionsError),
 
-    Help(HelpString)
-}
\ No newline at end of file
+    Help(HelpString),
+
+    Man(ManPage),
+
+    Version(VersionString),
+
+    Completions(Completions)
+}
+#[cfg(test)]
+pub mod test {
+    use std::ffi::OsStr;
+
+    use crate::options::flags;
+    use crate::options::parser::{MatchedFlags, Strictness};
+
+    /// Parse the given arguments with every flag of search, for the tests
+    /// of the deduce functions.
+    pub fn parse_for_test(inputs: &[&'static str]) -> MatchedFlags<'static> {
+        flags::ALL_ARGS.parse(inputs.iter().map(|input| OsStr::new(*input)), Strictness::UseLastArgument)
+            .expect("the arguments don’t parse")
+            .flags
+    }
+}
+
+#[cfg(test)]
+mod anchor_test {
+    use super::*;
+    use std::ffi::OsString;
+
+    use crate::options::test::parse_for_test;
+
+    struct NoVars;
+
+    impl Vars for NoVars {
+        fn get(&self, _name: &'static str) -> Option<OsString> {
+            None
+        }
+    }
+
+    fn anchored(args: &[&'static str]) -> Options {
+        let mut options = Options::deduce(&parse_for_test(args), &NoVars).unwrap();
+        options.anchor_paths(Path::new("/start"));
+        options
+    }
+
+    #[test]
+    fn files_from() {
+        assert_eq!(anchored(&["--files-from", "list"]).files_from, Some(FilesFrom::File(PathBuf::from("/start/list"))));
+        assert_eq!(anchored(&["--files-from", "/list"]).files_from, Some(FilesFrom::File(PathBuf::from("/list"))));
+        assert_eq!(anchored(&["--files-from", "-"]).files_from, Some(FilesFrom::Stdin));
+    }
+
+    #[test]
+    fn relative_to() {
+        assert_eq!(anchored(&["--relative-to", "dir"]).file_style.path_mode, PathMode::RelativeTo(PathBuf::from("/start/dir")));
+        assert_eq!(anchored(&["--absolute-path"]).file_style.path_mode, PathMode::Absolute);
+    }
+}
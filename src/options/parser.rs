@@ -40,7 +40,7 @@ pub enum Strictness {
     UseLastArgument
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug)]
 pub enum TakesValue {
     /// This flags has to take a values
     Necessary(Option<Values>),
@@ -52,7 +52,43 @@ pub enum TakesValue {
     Forbidden
 }
 
-#[derive(PartialEq)]
+/// The section of the help text an argument is documented in.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Section {
+    Meta,
+    Filtering,
+    Traversal,
+    Display,
+    Limits,
+}
+
+impl Section {
+    /// Every section, in the order they are documented.
+    pub const ALL: &'static [Section] = &[Section::Meta, Section::Filtering, Section::Traversal, Section::Display, Section::Limits];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Meta      => "META OPTIONS",
+            Self::Filtering => "FILTERING OPTIONS",
+            Self::Traversal => "TRAVERSAL OPTIONS",
+            Self::Display   => "DISPLAY OPTIONS",
+            Self::Limits    => "LIMIT OPTIONS",
+        }
+    }
+
+    /// What the flags of the section are about, for the help summary.
+    pub fn summary(self) -> &'static str {
+        match self {
+            Self::Meta      => "help, version, manual page and completion scripts",
+            Self::Filtering => "which files match: name, extension, size, type, permissions, owner, dates",
+            Self::Traversal => "how the paths are walked and how the errors are reported",
+            Self::Display   => "how the paths are coloured, quoted and printed",
+            Self::Limits    => "when the search stops: after N matches, or at the first one",
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
 pub struct Arg {
     /// The short name for the argument if it has one
     pub short: Option<ShortArg>,
@@ -61,7 +97,16 @@ pub struct Arg {
     pub long: LongArg,
 
     /// Whether this flag takes value or not.
-    pub takes_value: TakesValue
+    pub takes_value: TakesValue,
+
+    /// The name of the value in the help text, if the flag takes one.
+    pub value_name: Option<&'static str>,
+
+    /// The section of the help text the flag is documented in.
+    pub section: Section,
+
+    /// A one-line description of the flag for the help text.
+    pub description: &'static str
 }
 
 impl fmt::Display for Arg {
@@ -192,7 +237,7 @@ impl Args {
                     //   -abx def       => ‘a’, ‘b’, ‘x=def’
                     //   -y             => error
 
-                    for (index, byte) in bytes.into_iter().enumerate().skip(1) {
+                    for (index, byte) in bytes.iter().enumerate().skip(1) {
                         let arg = self.lookup_short(*byte)?;
                         let flag = Flag::Short(*byte);
 
@@ -310,6 +355,15 @@ impl <'a> MatchedFlags<'a> {
         }
     }
 
+    /// Get the values of every occurence of a repeatable flag, in order.
+    /// Repeating such a flag is never an error, whatever the strictness.
+    pub fn get_all(&self, arg: &'static Arg) -> Vec<&OsStr> {
+        self.flags.iter()
+            .filter(|tuple| tuple.0.matches(arg))
+            .filter_map(|tuple| tuple.1)
+            .collect()
+    }
+
     pub fn count(&self, arg: &Arg) -> usize {
         self.flags.iter()
             .filter(|tuple| tuple.0.matches(arg))
@@ -322,11 +376,11 @@ impl <'a> MatchedFlags<'a> {
     }
 }
 
-fn os_str_to_bytes<'b>(os_str: &'b OsStr) -> &'b [u8] {
+fn os_str_to_bytes(os_str: &OsStr) -> &[u8] {
     os_str.as_bytes()
 }
 
-fn bytes_to_os_str<'b>(bytes: &'b [u8]) -> &'b OsStr {
+fn bytes_to_os_str(bytes: &[u8]) -> &OsStr {
     OsStr::from_bytes(bytes)
 }
 
@@ -431,10 +485,10 @@ mod parser_test {
     const TEST_ARGS_VALUE: Values = &["test"];
 
     const TEST_ARGS: &[&Arg] = &[
-        &Arg { short: Some(b'l'), long: "long", takes_value: TakesValue::Forbidden },
-        &Arg { short: Some(b's'), long: "short", takes_value: TakesValue::Forbidden },
-        &Arg { short: Some(b'c'), long: "count", takes_value: TakesValue::Necessary(None) },
-        &Arg { short: Some(b't'), long: "type", takes_value: TakesValue::Necessary(Some(TEST_ARGS_VALUE)) },
+        &Arg { short: Some(b'l'), long: "long", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Meta, description: "" },
+        &Arg { short: Some(b's'), long: "short", takes_value: TakesValue::Forbidden, value_name: None, section: Section::Meta, description: "" },
+        &Arg { short: Some(b'c'), long: "count", takes_value: TakesValue::Necessary(None), value_name: Some("COUNT"), section: Section::Meta, description: "" },
+        &Arg { short: Some(b't'), long: "type", takes_value: TakesValue::Necessary(Some(TEST_ARGS_VALUE)), value_name: Some("TYPE"), section: Section::Meta, description: "" },
     ];
 
     test_parser!(empty: [] => flags: [], frees: []);
@@ -495,4 +549,15 @@ mod parser_test {
     test_parser!(unknown_short_equal: ["-u=anything"] => error UnknownShortArgument { short: b'u' });
     test_parser!(unknown_short_equal_2nd: ["-lu=anything"] => error UnknownShortArgument { short: b'u' });
 
+    // Repeated args
+    static COUNT: Arg = Arg { short: Some(b'c'), long: "count", takes_value: TakesValue::Necessary(None), value_name: Some("COUNT"), section: Section::Meta, description: "" };
+
+    #[test]
+    fn get_all_in_strict_mode() {
+        let inputs = ["-c", "1", "--count=2", "-l"].iter().map(OsStr::new);
+        let matches = Args(TEST_ARGS).parse(inputs, Strictness::ForbiddenRedudantArguments).unwrap();
+
+        assert_eq!(matches.flags.get_all(&COUNT), vec![OsStr::new("1"), OsStr::new("2")]);
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+use crate::options::{errors::OptionsError, flags, parser::MatchedFlags};
+use crate::output::report::{ErrorFormat, Report};
+
+impl Report {
+    /// Deduce how the errors are reported from the given matches flags.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        let skip_denied = matches.has(&flags::SKIP_DENIED)?;
+        let quiet_errors = matches.has(&flags::QUIET_ERRORS)?;
+        let format = ErrorFormat::deduce(matches)?;
+
+        Ok(Self { skip_denied, quiet_errors, format })
+    }
+}
+
+impl ErrorFormat {
+    fn deduce(matches: &MatchedFlags) -> Result<Self, OptionsError> {
+        match matches.get(&flags::ERROR_FORMAT)? {
+            Some(word) if word == "text" => Ok(Self::Text),
+            Some(word) if word == "json" => Ok(Self::Json),
+            Some(word) => Err(OptionsError::BadArgument(&flags::ERROR_FORMAT, word.into())),
+            None => Ok(Self::default()),
+        }
+    }
+}
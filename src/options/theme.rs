@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+
+use crate::options::{errors::OptionsError, flags, parser::MatchedFlags, vars::{self, Vars}};
+use crate::theme::{ColourSupport, Definitions, Options as ThemeOptions, ThemeFile, UseColours};
+
+impl ThemeOptions {
+    /// Deduce the theme options from the given matches flags and the
+    /// environment.
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Self, OptionsError> {
+        let file = match matches.get(&flags::THEME)? {
+            Some(name) => {
+                let name = name.to_str()
+                    .ok_or_else(|| OptionsError::BadArgument(&flags::THEME, name.into()))?;
+
+                let file = ThemeFile::load(name, config_dir(vars).as_deref())
+                    .map_err(OptionsError::BadTheme)?;
+                Some(file)
+            },
+            None => None,
+        };
+
+        let use_colours = UseColours::deduce(matches, vars)?;
+        let colour_support = ColourSupport::deduce(vars);
+        let definitions = Definitions::deduce(vars);
+
+        Ok(Self { use_colours, colour_support, file, definitions })
+    }
+}
+
+impl UseColours {
+    /// Deduce when to use colours from the `--color` flag. Without it, or
+    /// with `--color=auto`, `NO_COLOR` disables the colours, then
+    /// `CLICOLOR_FORCE` forces them and a dumb terminal disables them.
+    fn deduce<V: Vars>(matches: &MatchedFlags, vars: &V) -> Result<Self, OptionsError> {
+        match matches.get(&flags::COLOR)? {
+            Some(word) if word == "always" => return Ok(Self::Always),
+            Some(word) if word == "never" => return Ok(Self::Never),
+            Some(word) if word == "auto" => (),
+            Some(word) => return Err(OptionsError::BadArgument(&flags::COLOR, word.into())),
+            None => (),
+        }
+
+        if vars.get(vars::NO_COLOR).map_or(false, |v| !v.is_empty()) {
+            Ok(Self::Never)
+        } else if vars.get(vars::CLICOLOR_FORCE).map_or(false, |v| !v.is_empty() && v != "0") {
+            Ok(Self::Always)
+        } else if vars.get(vars::TERM).map_or(false, |v| v == "dumb") {
+            Ok(Self::Never)
+        } else {
+            Ok(Self::Automatic)
+        }
+    }
+}
+
+/// The prefixes of the `TERM` values of the terminals that support true
+/// colours even without `COLORTERM`.
+static TRUE_COLOUR_TERMS: &[&str] = &["alacritty", "foot", "xterm-kitty", "wezterm", "contour", "xterm-ghostty"];
+
+/// The prefixes of the `TERM` values of the terminals that support the
+/// 256-colour palette, even when their name doesn’t say so.
+static ANSI256_TERMS: &[&str] = &["xterm", "screen", "tmux", "rxvt", "putty", "konsole", "gnome", "st-", "iterm"];
+
+impl ColourSupport {
+    /// Guess the colours the terminal supports from `COLORTERM`, from the
+    /// variables set by the known terminals, then from `TERM`. Only the
+    /// terminals that aren’t known fall back to the 16 colours, and without
+    /// any of them nothing is known and nothing is downgraded.
+    fn deduce<V: Vars>(vars: &V) -> Self {
+        let colorterm = vars.get(vars::COLORTERM).filter(|v| !v.is_empty());
+        if colorterm.as_ref().map_or(false, |v| v == "truecolor" || v == "24bit") {
+            return Self::TrueColour;
+        }
+
+        let term_program = vars.get(vars::TERM_PROGRAM).and_then(|v| v.into_string().ok());
+        match term_program.as_deref() {
+            Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty") => return Self::TrueColour,
+            Some("Apple_Terminal") => return Self::Ansi256,
+            _ => (),
+        }
+
+        if vars.get(vars::KITTY_WINDOW_ID).is_some() || vars.get(vars::WT_SESSION).is_some() {
+            return Self::TrueColour;
+        }
+
+        // VTE based terminals, such as GNOME Terminal, support them since 0.36.
+        let vte_version = vars.get(vars::VTE_VERSION)
+            .and_then(|v| v.into_string().ok())
+            .and_then(|v| v.parse::<u32>().ok());
+        if vte_version.map_or(false, |version| version >= 3600) {
+            return Self::TrueColour;
+        }
+
+        match vars.get(vars::TERM).and_then(|v| v.into_string().ok()) {
+            Some(term) if term.ends_with("-direct") => Self::TrueColour,
+            Some(term) if TRUE_COLOUR_TERMS.iter().any(|prefix| term.starts_with(prefix)) => Self::TrueColour,
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            Some(term) if ANSI256_TERMS.iter().any(|prefix| term.starts_with(prefix)) => Self::Ansi256,
+            _ if colorterm.is_some() => Self::Ansi256,
+            Some(_) => Self::Ansi16,
+            None => Self::TrueColour,
+        }
+    }
+}
+
+impl Definitions {
+    /// Read the colour definitions from the environment. Definitions that
+    /// aren’t valid unicode are ignored.
+    fn deduce<V: Vars>(vars: &V) -> Self {
+        let ls = vars.get(vars::LS_COLORS).and_then(|v| v.into_string().ok());
+        let search = vars.get(vars::SEARCH_COLORS).and_then(|v| v.into_string().ok());
+
+        Self { ls, search }
+    }
+}
+
+/// The configuration directory of search: `$XDG_CONFIG_HOME/search`, or
+/// `$HOME/.config/search`.
+fn config_dir<V: Vars>(vars: &V) -> Option<PathBuf> {
+    let base = match vars.get(vars::XDG_CONFIG_HOME) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(vars.get(vars::HOME)?).join(".config"),
+    };
+
+    Some(base.join("search"))
+}
+
+#[cfg(test)]
+mod theme_test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::ffi::OsString;
+
+    use crate::options::test::parse_for_test;
+
+    struct MockVars(HashMap<&'static str, &'static str>);
+
+    impl MockVars {
+        fn new(vars: &[(&'static str, &'static str)]) -> Self {
+            Self(vars.iter().copied().collect())
+        }
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            self.0.get(name).map(OsString::from)
+        }
+    }
+
+    fn use_colours(args: &[&'static str], vars: &[(&'static str, &'static str)]) -> UseColours {
+        UseColours::deduce(&parse_for_test(args), &MockVars::new(vars)).unwrap()
+    }
+
+    #[test]
+    fn automatic_by_default() {
+        assert_eq!(use_colours(&[], &[]), UseColours::Automatic);
+    }
+
+    #[test]
+    fn no_color() {
+        assert_eq!(use_colours(&[], &[("NO_COLOR", "1")]), UseColours::Never);
+        assert_eq!(use_colours(&[], &[("NO_COLOR", "")]), UseColours::Automatic);
+    }
+
+    #[test]
+    fn no_color_wins_over_clicolor_force() {
+        assert_eq!(use_colours(&[], &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]), UseColours::Never);
+    }
+
+    #[test]
+    fn clicolor_force() {
+        assert_eq!(use_colours(&[], &[("CLICOLOR_FORCE", "1"), ("TERM", "dumb")]), UseColours::Always);
+        assert_eq!(use_colours(&[], &[("CLICOLOR_FORCE", "0")]), UseColours::Automatic);
+    }
+
+    #[test]
+    fn dumb_terminal() {
+        assert_eq!(use_colours(&[], &[("TERM", "dumb")]), UseColours::Never);
+    }
+
+    #[test]
+    fn flag_wins_over_variables() {
+        assert_eq!(use_colours(&["--color=always"], &[("NO_COLOR", "1")]), UseColours::Always);
+        assert_eq!(use_colours(&["--color=never"], &[("CLICOLOR_FORCE", "1")]), UseColours::Never);
+    }
+
+    #[test]
+    fn auto_flag_reads_variables() {
+        assert_eq!(use_colours(&["--color=auto"], &[("NO_COLOR", "1")]), UseColours::Never);
+    }
+
+    #[test]
+    fn last_flag_wins() {
+        assert_eq!(use_colours(&["--color=never", "--color=always"], &[]), UseColours::Always);
+    }
+
+    fn colour_support(vars: &[(&'static str, &'static str)]) -> ColourSupport {
+        ColourSupport::deduce(&MockVars::new(vars))
+    }
+
+    #[test]
+    fn colour_support_from_term() {
+        assert_eq!(colour_support(&[("TERM", "xterm")]), ColourSupport::Ansi256);
+        assert_eq!(colour_support(&[("TERM", "screen")]), ColourSupport::Ansi256);
+        assert_eq!(colour_support(&[("TERM", "xterm-256color")]), ColourSupport::Ansi256);
+        assert_eq!(colour_support(&[("TERM", "alacritty")]), ColourSupport::TrueColour);
+        assert_eq!(colour_support(&[("TERM", "linux")]), ColourSupport::Ansi16);
+        assert_eq!(colour_support(&[("TERM", "vt100")]), ColourSupport::Ansi16);
+    }
+
+    #[test]
+    fn colour_support_from_colorterm() {
+        assert_eq!(colour_support(&[("TERM", "screen"), ("COLORTERM", "truecolor")]), ColourSupport::TrueColour);
+        assert_eq!(colour_support(&[("TERM", "linux"), ("COLORTERM", "yes")]), ColourSupport::Ansi256);
+    }
+
+    #[test]
+    fn colour_support_from_known_terminals() {
+        assert_eq!(colour_support(&[("TERM", "xterm"), ("TERM_PROGRAM", "iTerm.app")]), ColourSupport::TrueColour);
+        assert_eq!(colour_support(&[("TERM", "xterm"), ("VTE_VERSION", "6800")]), ColourSupport::TrueColour);
+        assert_eq!(colour_support(&[("TERM", "xterm"), ("WT_SESSION", "1")]), ColourSupport::TrueColour);
+    }
+
+    #[test]
+    fn colour_support_unknown() {
+        assert_eq!(colour_support(&[]), ColourSupport::TrueColour);
+    }
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use crate::fs::files_from::FilesFrom;
+use crate::options::{errors::OptionsError, flags, parser::MatchedFlags};
+
+/// Deduce the directory the search runs from, if the user gave one.
+pub fn deduce_base_directory(matches: &MatchedFlags) -> Result<Option<PathBuf>, OptionsError> {
+    Ok(matches.get(&flags::BASE_DIRECTORY)?.map(PathBuf::from))
+}
+
+impl FilesFrom {
+    /// Deduce where the list of paths is read from, if anywhere, from the
+    /// given matches flags.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Option<Self>, OptionsError> {
+        match matches.get(&flags::FILES_FROM)? {
+            Some(path) if path == "-" => Ok(Some(Self::Stdin)),
+            Some(path) => Ok(Some(Self::File(PathBuf::from(path)))),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::ffi::OsString;
+
+/// Mockable wrapper for `std::env::var_os`.
+pub trait Vars {
+    fn get(&self, name: &'static str) -> Option<OsString>;
+}
+
+/// Environment variable used to colour files, both by their filesystem
+/// type and by their name.
+pub static LS_COLORS: &str = "LS_COLORS";
+
+/// Environment variable used to find the configuration directory.
+pub static XDG_CONFIG_HOME: &str = "XDG_CONFIG_HOME";
+
+/// Environment variable used to find the configuration directory when
+/// `XDG_CONFIG_HOME` isn’t set.
+pub static HOME: &str = "HOME";
+
+/// Environment variable used to colour the search-specific parts of the
+/// output, such as the parent path and the name matches.
+pub static SEARCH_COLORS: &str = "SEARCH_COLORS";
+
+/// Environment variable used to disable the colours when it’s set to a
+/// non-empty value, see <https://no-color.org>.
+pub static NO_COLOR: &str = "NO_COLOR";
+
+/// Environment variable used to force the colours when it’s set to a
+/// value other than `0`, even when the output isn’t a terminal.
+pub static CLICOLOR_FORCE: &str = "CLICOLOR_FORCE";
+
+/// Environment variable used to guess the colours the terminal supports.
+pub static TERM: &str = "TERM";
+
+/// Environment variable used by terminals that support true colours.
+pub static COLORTERM: &str = "COLORTERM";
+
+/// Environment variable set by terminals to tell their name, used to guess
+/// whether they support hyperlinks and true colours.
+pub static TERM_PROGRAM: &str = "TERM_PROGRAM";
+
+/// Environment variable set by kitty, which supports hyperlinks and true
+/// colours.
+pub static KITTY_WINDOW_ID: &str = "KITTY_WINDOW_ID";
+
+/// Environment variable set by Windows Terminal, which supports hyperlinks
+/// and true colours.
+pub static WT_SESSION: &str = "WT_SESSION";
+
+/// Environment variable set by the VTE based terminals to their version.
+pub static VTE_VERSION: &str = "VTE_VERSION";
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::options::errors::OptionsError;
+use crate::options::parser::MatchedFlags;
+use crate::options::flags;
+
+/// A struct that represents the version string.
+/// The build metadata is embedded at compile time by `build.rs`.
+pub struct VersionString {
+    /// Whether to print the machine-readable `key=value` form.
+    pub verbose: bool,
+}
+
+impl VersionString {
+    /// Deduce a VersionString from the given matches flags. `--verbose`
+    /// only changes the version string, so it is rejected without
+    /// `--version`.
+    pub fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        if matches.count(&flags::VERSION) == 0 {
+            if matches.has(&flags::VERBOSE)? {
+                return Err(OptionsError::Useless(&flags::VERBOSE, &flags::VERSION));
+            }
+
+            return Ok(None);
+        }
+
+        let verbose = matches.has(&flags::VERBOSE)?;
+        Ok(Some(Self { verbose }))
+    }
+}
+
+/// The enabled cargo features, or `none`.
+fn features() -> &'static str {
+    match env!("SEARCH_FEATURES") {
+        "" => "none",
+        features => features,
+    }
+}
+
+/// Implement the Display trait for VersionString.
+/// This allows us to print the version string.
+impl fmt::Display for VersionString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.verbose {
+            writeln!(f, "name={}", env!("CARGO_PKG_NAME"))?;
+            writeln!(f, "version={}", env!("CARGO_PKG_VERSION"))?;
+            writeln!(f, "commit={}", env!("SEARCH_GIT_COMMIT"))?;
+            writeln!(f, "build_date={}", env!("SEARCH_BUILD_DATE"))?;
+            writeln!(f, "features={}", features())?;
+            writeln!(f, "target={}", env!("SEARCH_TARGET"))?;
+            writeln!(f, "rustc={}", env!("SEARCH_RUSTC_VERSION"))
+        } else {
+            writeln!(f, "search {} ({} {})", env!("CARGO_PKG_VERSION"), env!("SEARCH_GIT_COMMIT"), env!("SEARCH_BUILD_DATE"))?;
+            writeln!(f, "features: {}", features())
+        }
+    }
+}
+
+#[cfg(test)]
+mod version_test {
+    use super::*;
+    use crate::options::test::parse_for_test;
+
+    #[test]
+    fn no_version() {
+        assert!(matches!(VersionString::deduce(&parse_for_test(&[])), Ok(None)));
+    }
+
+    #[test]
+    fn verbose() {
+        let version = VersionString::deduce(&parse_for_test(&["--version", "--verbose"])).unwrap().unwrap();
+        assert!(version.verbose);
+        assert!(version.to_string().contains(&format!("\nfeatures={}\n", features())));
+    }
+
+    #[test]
+    fn verbose_without_version() {
+        let error = VersionString::deduce(&parse_for_test(&["--verbose"])).err().unwrap();
+        assert_eq!(error.to_string(), "Flag --verbose is useless without flag --version (-v)");
+    }
+}
@@ -1,26 +1,85 @@
-use ansi_term::{ANSIString, Style};
+use ansi_term::{ANSIByteString, Style};
 
 
-pub fn escape(string: String, bits: &mut Vec<ANSIString<'_>>, good: Style, bad: Style) {
-    if string.chars().all(|c| c >= 0x20 as char && c != 0x7f as char) {
-        bits.push(good.paint(string));
+/// Paint the bytes of a file name or path, escaping the ones that can’t be
+/// displayed with the `bad` style: control characters are written as Rust
+/// escapes them, and bytes that aren’t valid UTF-8 as `\xff`.
+pub fn escape(bytes: &[u8], bits: &mut Vec<ANSIByteString<'_>>, good: Style, bad: Style) {
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(string) => (string, &[][..]),
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                let invalid_len = e.error_len().unwrap_or(after.len());
+
+                (std::str::from_utf8(valid).unwrap_or_default(), &after[..invalid_len])
+            },
+        };
+
+        escape_str(valid, bits, good, bad);
+
+        for byte in invalid {
+            bits.push(bad.paint(format!("\\x{:02x}", byte).into_bytes()));
+        }
+
+        rest = &rest[valid.len() + invalid.len()..];
+    }
+}
+
+fn escape_str(string: &str, bits: &mut Vec<ANSIByteString<'_>>, good: Style, bad: Style) {
+    if !string.chars().any(char::is_control) {
+        if !string.is_empty() {
+            bits.push(good.paint(string.as_bytes().to_vec()));
+        }
         return;
     }
 
     for c in string.chars() {
         // The `escape_default` method on `char` is *almost* what we want here, but
         // it still escapes non-ASCII UTF-8 characters, which are still printable.
+        // The C1 controls, such as the one-character CSI U+009B, are escaped
+        // like the ASCII ones, as terminals act on them too.
 
-        if c >= 0x20 as char && c != 0x7f as char {
+        if !c.is_control() {
             // TODO: This allocates way too much,
             // hence the `all` check above.
             let mut s = String::new();
             s.push(c);
-            bits.push(good.paint(s));
+            bits.push(good.paint(s.into_bytes()));
         }
         else {
             let s = c.escape_default().collect::<String>();
-            bits.push(bad.paint(s));
+            bits.push(bad.paint(s.into_bytes()));
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod escape_test {
+    use super::*;
+
+    fn escaped(bytes: &[u8]) -> Vec<u8> {
+        let mut bits = Vec::new();
+        escape(bytes, &mut bits, Style::default(), Style::default());
+        bits.iter().flat_map(|bit| bit.to_vec()).collect()
+    }
+
+    #[test]
+    fn printable() {
+        assert_eq!(escaped("héllo wörld".as_bytes()), "héllo wörld".as_bytes());
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(escaped(b"a\nb\x1b"), b"a\\nb\\u{1b}");
+        assert_eq!(escaped("a\u{9b}31mb\u{85}".as_bytes()), b"a\\u{9b}31mb\\u{85}");
+        assert_eq!(escaped(b"a\x7f"), b"a\\u{7f}");
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(escaped(b"a\xffb\xc3"), b"a\\xffb\\xc3");
+    }
+}
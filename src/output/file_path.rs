@@ -1,20 +1,68 @@
-use std::path::Path;
-use ansi_term::{ANSIString, Style};
-use crate::fs::file::File;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Component, Path, PathBuf};
+use ansi_term::{ANSIByteString, Style};
+use crate::fs::file::{File, FileTarget};
 
-use crate::output::escape;
+use crate::output::hyperlink::Hyperlinks;
+use crate::output::quoting::{Quoting, QuotingStyle};
 use crate::output::render::FiletypeColours;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
 pub struct Options {
+    /// How the paths are quoted and escaped.
+    pub quoting: QuotingStyle,
 
+    /// If present, the paths are wrapped in hyperlinks to the files.
+    pub hyperlinks: Option<Hyperlinks>,
+
+    /// How the directories leading to the files are printed.
+    pub path_mode: PathMode,
+
+    /// The directory the relative paths are resolved from, to print them
+    /// as absolute paths or relative to another directory.
+    pub current_dir: PathBuf,
+}
+
+/// How the directories leading to the files are printed.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub enum PathMode {
+    /// As they were reached from the paths given by the user.
+    #[default]
+    AsFound,
+
+    /// Without the `./` prefix of the ones reached from `.`.
+    StripCwdPrefix,
+
+    /// As absolute paths. They are made absolute without resolving the
+    /// symlinks or the `..` components.
+    Absolute,
+
+    /// Relative to the given directory.
+    RelativeTo(PathBuf),
 }
 
 impl Options {
-    pub fn for_file<'a, 'dir, C>(self, file: &'a File<'dir>, colours: &'a C) -> FilePath<'a, 'dir, C> {
+    /// The directory printed before the name of a file, from its parent
+    /// directory as it was reached.
+    fn display_parent<'p>(&self, parent: &'p Path) -> Cow<'p, Path> {
+        match &self.path_mode {
+            PathMode::AsFound        => Cow::Borrowed(parent),
+            PathMode::StripCwdPrefix => Cow::Borrowed(parent.strip_prefix(".").unwrap_or(parent)),
+            PathMode::Absolute       => Cow::Owned(absolute(&self.current_dir, parent)),
+            PathMode::RelativeTo(dir) => {
+                Cow::Owned(relative(&absolute(&self.current_dir, dir), &absolute(&self.current_dir, parent)))
+            },
+        }
+    }
+
+    pub fn for_file<'a, 'dir, C>(&'a self, file: &'a File<'dir>, colours: &'a C) -> FilePath<'a, 'dir, C> {
         FilePath { 
             file,
             colours,
+            highlights: Vec::new(),
             options: self 
         }
     }
@@ -24,75 +72,209 @@ pub struct FilePath<'a, 'dir, C> {
     file: &'a File<'dir>,
 
     colours: &'a C,
+
+    /// The byte ranges of the file name to paint with the highlight style.
+    highlights: Vec<Range<usize>>,
     
-    options: Options
+    options: &'a Options
 }
 
 impl<'a, 'dir, C: Colours> FilePath<'a, 'dir, C> {
-    pub fn paint(&self) -> Vec<ANSIString<'static>> {
+    /// Highlight the given byte ranges of the file name, such as the
+    /// matches of the name filter.
+    pub fn with_highlights(mut self, highlights: Vec<Range<usize>>) -> Self {
+        self.highlights = highlights;
+        self
+    }
+
+    pub fn paint(&self) -> Vec<ANSIByteString<'static>> {
         let mut bits = Vec::new();
 
+        let parent = match self.file.parent_dir {
+            Some(parent_dir) => Some(parent_dir.path.as_path()),
+            None => self.file.path.parent(),
+        };
+        let parent = parent.map(|parent| self.options.display_parent(parent));
+
+        let shown_path = match &parent {
+            Some(parent) => Cow::Owned(parent.join(&self.file.name)),
+            None => Cow::Borrowed(self.file.path.as_path()),
+        };
+        let quoting = self.options.quoting.quoting(shown_path.as_os_str().as_bytes());
+
+        if let Some(hyperlinks) = &self.options.hyperlinks {
+            bits.push(Style::default().paint(hyperlinks.start(&self.file.path)));
+        }
+
+        add_quote(&mut bits, quoting.open());
+
         // Add parents bits
-        if self.file.parent_dir.is_none() {
-            if let Some(parent) = self.file.path.parent() {
-                self.add_parent_bits(&mut bits, parent);
-            }
-        } else {
-            self.add_parent_bits(&mut bits, &self.file.parent_dir.unwrap().path);
+        if let Some(parent) = &parent {
+            self.add_parent_bits(&mut bits, parent, self.colours.parent_path(), quoting);
         }
 
         if ! self.file.name.is_empty() {
-            for bit in self.coloured_file_name() {
+            for bit in self.coloured_file_name(quoting) {
                 bits.push(bit);
             }
         }
 
-        bits.into()
+        add_quote(&mut bits, quoting.close());
+
+        if self.options.hyperlinks.is_some() {
+            bits.push(Style::default().paint(Hyperlinks::END));
+        }
+
+        if self.file.is_link() {
+            self.add_link_target_bits(&mut bits);
+        }
+
+        bits
     }
 
-    fn add_parent_bits(&self, bits: &mut Vec<ANSIString<'_>>, parent: &Path) {
+    fn add_parent_bits(&self, bits: &mut Vec<ANSIByteString<'_>>, parent: &Path, style: Style, quoting: Quoting) {
         let coconut = parent.components().count();
 
         if coconut == 1 && parent.has_root() {
-            bits.push(self.colours.symlink_path().paint(std::path::MAIN_SEPARATOR.to_string()));
+            bits.push(style.paint(std::path::MAIN_SEPARATOR.to_string().into_bytes()));
         }
         else if coconut >= 1 {
-            escape(
-                parent.to_string_lossy().to_string(),
+            quoting.paint(
+                parent.as_os_str().as_bytes(),
                 bits,
-                self.colours.symlink_path(),
+                style,
                 self.colours.control_char(),
             );
-            bits.push(self.colours.symlink_path().paint(std::path::MAIN_SEPARATOR.to_string()));
+            bits.push(style.paint(std::path::MAIN_SEPARATOR.to_string().into_bytes()));
+        }
+    }
+
+    /// Add the ` -> target` bits of a symlink. The target of a broken link
+    /// is painted entirely with the broken styles.
+    fn add_link_target_bits(&self, bits: &mut Vec<ANSIByteString<'_>>) {
+        match self.file.link_target() {
+            FileTarget::Ok(target) => {
+                bits.push(self.colours.normal_arrow().paint(&b" -> "[..]));
+
+                let quoting = self.options.quoting.quoting(target.path.as_os_str().as_bytes());
+                add_quote(bits, quoting.open());
+
+                if let Some(parent) = target.path.parent() {
+                    self.add_parent_bits(bits, parent, self.colours.symlink_path(), quoting);
+                }
+
+                if !target.name.is_empty() {
+                    bits.extend(self.options.for_file(&target, self.colours).coloured_file_name(quoting));
+                }
+
+                add_quote(bits, quoting.close());
+            },
+            FileTarget::Broken(path) => {
+                bits.push(self.colours.broken_symlink().paint(&b" -> "[..]));
+
+                let quoting = self.options.quoting.quoting(path.as_os_str().as_bytes());
+                add_quote(bits, quoting.open());
+
+                quoting.paint(
+                    path.as_os_str().as_bytes(),
+                    bits,
+                    self.colours.broken_filename(),
+                    self.colours.broken_control_char(),
+                );
+
+                add_quote(bits, quoting.close());
+            },
+            FileTarget::Err(_) => {},
         }
     }
 
-    fn coloured_file_name<'unused>(&self) -> Vec<ANSIString<'unused>> {
+    /// The bits of the file name. The name is split at the highlighted
+    /// ranges before being escaped, so control characters inside a match
+    /// are still painted as control characters.
+    fn coloured_file_name<'unused>(&self, quoting: Quoting) -> Vec<ANSIByteString<'unused>> {
         let file_style = self.style();
+        let name = self.file.name.as_bytes();
         let mut bits = Vec::new();
+        let mut start = 0;
 
-        escape(
-            self.file.name.clone(),
-            &mut bits,
-            file_style,
-            self.colours.control_char(),
-        );
+        for range in &self.highlights {
+            if range.start < start || range.end > name.len() {
+                continue;
+            }
+
+            if range.start > start {
+                quoting.paint(&name[start..range.start], &mut bits, file_style, self.colours.control_char());
+            }
+
+            quoting.paint(&name[range.clone()], &mut bits, self.colours.match_highlight(), self.colours.control_char());
+            start = range.end;
+        }
+
+        if start < name.len() {
+            quoting.paint(&name[start..], &mut bits, file_style, self.colours.control_char());
+        }
 
         bits
     }
 
+
+    /// The style of the file name. The executable bits need the metadata of
+    /// the file, so they are only checked when they change the style.
     pub fn style(&self) -> Style {
+        let executable = self.colours.executable_file();
+
         match self.file {
             f if f.is_directory()        => self.colours.directory(),
+            f if f.is_link()             => self.colours.symlink(),
+            f if executable != Style::default() && f.is_executable_file() => executable,
+            f if f.file_type.is_fifo()   => self.colours.pipe(),
+            f if f.file_type.is_socket() => self.colours.socket(),
+            f if f.file_type.is_block_device() => self.colours.block_device(),
+            f if f.file_type.is_char_device()  => self.colours.char_device(),
             f if ! f.is_file()           => self.colours.special(),
             _                            => self.colours.colour_file(self.file),
         }
     }
 }
 
+/// The path joined to the given directory, without its `.` components.
+fn absolute(current_dir: &Path, path: &Path) -> PathBuf {
+    current_dir.join(path)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+/// The path to go from one absolute directory to an absolute path.
+fn relative(base: &Path, path: &Path) -> PathBuf {
+    let base = base.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+    let common = base.iter().zip(&path).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &path[common..] {
+        relative.push(component);
+    }
+
+    relative
+}
+
+/// Add an opening or closing quote, unpainted, if the quoting has one.
+fn add_quote(bits: &mut Vec<ANSIByteString<'_>>, quote: &'static [u8]) {
+    if !quote.is_empty() {
+        bits.push(Style::default().paint(quote));
+    }
+}
+
 /// The set of colours that are needed to paint a file path.
 pub trait Colours: FiletypeColours {
 
+    /// The style to paint the directories leading to a file’s name.
+    fn parent_path(&self) -> Style;
+
     /// The style to paint the path of a symlink’s target, up to but not
     /// including the file’s name.
     fn symlink_path(&self) -> Style;
@@ -115,8 +297,147 @@ pub trait Colours: FiletypeColours {
     /// when the filename is being displayed as a broken link target.
     fn broken_control_char(&self) -> Style;
 
+    /// The style to paint the part of a file name matched by the name filter.
+    fn match_highlight(&self) -> Style;
+
     /// The style to paint a file that has its executable bit set.
     fn executable_file(&self) -> Style;
 
     fn colour_file(&self, file: &File<'_>) -> Style;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod file_path_test {
+    use super::*;
+    use std::ffi::OsString;
+
+    use crate::theme::Theme;
+
+    fn with_mode(path_mode: PathMode) -> Options {
+        Options { path_mode, current_dir: PathBuf::from("/home/me"), ..Options::default() }
+    }
+
+    #[test]
+    fn strip_cwd_prefix() {
+        let options = with_mode(PathMode::StripCwdPrefix);
+        assert_eq!(options.display_parent(Path::new("./src")), Path::new("src"));
+        assert_eq!(options.display_parent(Path::new(".")), Path::new(""));
+        assert_eq!(options.display_parent(Path::new("/etc")), Path::new("/etc"));
+    }
+
+    #[test]
+    fn absolute_path() {
+        let options = with_mode(PathMode::Absolute);
+        assert_eq!(options.display_parent(Path::new("./src")), Path::new("/home/me/src"));
+        assert_eq!(options.display_parent(Path::new("")), Path::new("/home/me"));
+        assert_eq!(options.display_parent(Path::new("/etc")), Path::new("/etc"));
+    }
+
+    #[test]
+    fn relative_to() {
+        let options = with_mode(PathMode::RelativeTo(PathBuf::from("/home/you/code")));
+        assert_eq!(options.display_parent(Path::new("./src")), Path::new("../../me/src"));
+
+        let options = with_mode(PathMode::RelativeTo(PathBuf::from("src")));
+        assert_eq!(options.display_parent(Path::new("./src/fs")), Path::new("fs"));
+        assert_eq!(options.display_parent(Path::new("./src")), Path::new(""));
+    }
+
+    /// The bits of the highlighted name, with their styles. The file is `/`
+    /// under another name, so it is painted as a directory.
+    fn highlighted(name: &str, highlights: &[(usize, usize)]) -> Vec<(Style, String)> {
+        let theme = Theme::default_theme();
+        let file = File::from_args(PathBuf::from("/"), None, OsString::from(name)).unwrap();
+        let options = Options::default();
+
+        options.for_file(&file, &theme)
+            .with_highlights(highlights.iter().map(|&(start, end)| start..end).collect())
+            .coloured_file_name(Quoting::Escaped)
+            .iter()
+            .map(|bit| (*bit.style_ref(), String::from_utf8_lossy(bit).into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn highlight_covering_a_control_char() {
+        let theme = Theme::default_theme();
+        let (highlight, control) = (theme.ui.match_highlight, theme.ui.control_char);
+
+        assert_eq!(highlighted("a\x07b", &[(0, 3)]), vec![
+            (highlight, String::from("a")),
+            (control,   String::from("\\u{7}")),
+            (highlight, String::from("b")),
+        ]);
+    }
+
+    #[test]
+    fn highlight_next_to_a_control_char() {
+        let theme = Theme::default_theme();
+        let (directory, highlight, control) = (theme.ui.filekinds.directory, theme.ui.match_highlight, theme.ui.control_char);
+
+        assert_eq!(highlighted("a\x07bc", &[(2, 3)]), vec![
+            (directory, String::from("a")),
+            (control,   String::from("\\u{7}")),
+            (highlight, String::from("b")),
+            (directory, String::from("c")),
+        ]);
+    }
+
+    #[test]
+    fn adjacent_highlights() {
+        let theme = Theme::default_theme();
+        let (directory, highlight) = (theme.ui.filekinds.directory, theme.ui.match_highlight);
+
+        assert_eq!(highlighted("abcd", &[(1, 2), (2, 3)]), vec![
+            (directory, String::from("a")),
+            (highlight, String::from("b")),
+            (highlight, String::from("c")),
+            (directory, String::from("d")),
+        ]);
+    }
+
+    #[test]
+    fn overlapping_and_out_of_bounds_highlights_are_skipped() {
+        let theme = Theme::default_theme();
+        let (directory, highlight) = (theme.ui.filekinds.directory, theme.ui.match_highlight);
+
+        assert_eq!(highlighted("abc", &[(0, 2), (1, 3), (2, 9)]), vec![
+            (highlight, String::from("ab")),
+            (directory, String::from("c")),
+        ]);
+    }
+
+    /// The style of a file name, with the given `LS_COLORS`.
+    fn style_with_ls_colors(ls: &str, path: &Path) -> Style {
+        let theme = crate::theme::Options {
+            use_colours: crate::theme::UseColours::Always,
+            colour_support: crate::theme::ColourSupport::TrueColour,
+            file: None,
+            definitions: crate::theme::Definitions { ls: Some(ls.to_string()), search: None },
+        }.to_theme(false);
+
+        let file = File::from_args(path.to_path_buf(), None, None).unwrap();
+        Options::default().for_file(&file, &theme).style()
+    }
+
+    #[test]
+    fn special_file_kinds() {
+        use ansi_term::Colour::*;
+        use crate::fs::test::TempDir;
+
+        let temp = TempDir::new("special-file-kinds");
+        let ls = "pi=31:so=32:bd=33:cd=34:ex=35";
+
+        let fifo = temp.0.join("fifo");
+        let c_path = std::ffi::CString::new(fifo.as_os_str().as_bytes()).unwrap();
+        // SAFETY: the path is a valid NUL-terminated string.
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        assert_eq!(style_with_ls_colors(ls, &fifo), Red.normal());
+
+        let socket = temp.0.join("socket");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        assert_eq!(style_with_ls_colors(ls, &socket), Green.normal());
+
+        assert_eq!(style_with_ls_colors(ls, Path::new("/dev/null")), Blue.normal());
+    }
+}
@@ -0,0 +1,137 @@
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+
+/// When to wrap the printed paths in terminal hyperlinks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ShowHyperlinks {
+    /// Link the paths, even when the output isn’t a terminal.
+    Always,
+
+    /// Link the paths when the output is a terminal known to support
+    /// hyperlinks.
+    Automatic,
+
+    /// Never link the paths.
+    #[default]
+    Never,
+}
+
+impl ShowHyperlinks {
+    /// The hyperlinks to add to the paths, if any. The output being a
+    /// terminal is only known at runtime, so `Automatic` is resolved here.
+    pub fn to_hyperlinks(self, isatty: bool) -> Option<Hyperlinks> {
+        match self {
+            Self::Always => Some(Hyperlinks::new()),
+            Self::Automatic if isatty => Some(Hyperlinks::new()),
+            _ => None,
+        }
+    }
+}
+
+/// The OSC 8 hyperlinks added around the printed paths, pointing to
+/// `file://host/absolute/path` URLs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlinks {
+    /// The name of the host, so the terminal can tell local files apart.
+    host: String,
+
+    /// The directory the relative paths are resolved from.
+    current_dir: PathBuf,
+}
+
+impl Hyperlinks {
+    /// The escape sequence that ends a hyperlink.
+    pub const END: &'static [u8] = b"\x1b]8;;\x1b\\";
+
+    pub fn new() -> Self {
+        let current_dir = std::env::current_dir().unwrap_or_default();
+
+        Self { host: hostname().unwrap_or_default(), current_dir }
+    }
+
+    /// The escape sequence that starts a hyperlink to the given path.
+    pub fn start(&self, path: &Path) -> Vec<u8> {
+        let mut start = b"\x1b]8;;".to_vec();
+        start.extend(self.url(path).into_bytes());
+        start.extend(b"\x1b\\");
+        start
+    }
+
+    /// The `file://` URL of a path, with the bytes that aren’t allowed in
+    /// a URL path percent-encoded.
+    pub fn url(&self, path: &Path) -> String {
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.current_dir.join(path)
+        };
+
+        let mut url = format!("file://{}", self.host);
+        let mut is_root = true;
+
+        for component in absolute.components() {
+            match component {
+                Component::RootDir | Component::CurDir => continue,
+                _ => url.push('/'),
+            }
+            is_root = false;
+
+            for byte in component.as_os_str().as_bytes() {
+                match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => url.push(char::from(*byte)),
+                    _ => url.push_str(&format!("%{:02X}", byte)),
+                }
+            }
+        }
+
+        if is_root {
+            url.push('/');
+        }
+
+        url
+    }
+}
+
+/// The name of the host, as given by `gethostname(2)`.
+fn hostname() -> Option<String> {
+    let mut buffer = [0_u8; 256];
+
+    // SAFETY: the buffer is valid for its whole length, and the last byte
+    // is left as a terminator in case the name is truncated.
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len() - 1) };
+    if result != 0 {
+        return None;
+    }
+
+    let len = buffer.iter().position(|byte| *byte == 0).unwrap_or(buffer.len());
+    String::from_utf8(buffer[..len].to_vec()).ok()
+}
+
+#[cfg(test)]
+mod hyperlink_test {
+    use super::*;
+
+    fn hyperlinks() -> Hyperlinks {
+        Hyperlinks { host: String::from("box"), current_dir: PathBuf::from("/home/me") }
+    }
+
+    #[test]
+    fn absolute() {
+        assert_eq!(hyperlinks().url(Path::new("/etc/hosts")), "file://box/etc/hosts");
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(hyperlinks().url(Path::new("./src/main.rs")), "file://box/home/me/src/main.rs");
+    }
+
+    #[test]
+    fn percent_encoded() {
+        assert_eq!(hyperlinks().url(Path::new("/a b/100%#é")), "file://box/a%20b/100%25%23%C3%A9");
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(hyperlinks().url(Path::new("/")), "file://box/");
+    }
+}
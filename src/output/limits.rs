@@ -0,0 +1,21 @@
+/// How many of the matched files are printed, and when the search stops.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// If present, the search stops once this many files have been printed.
+    pub max_results: Option<usize>,
+
+    /// Whether nothing is printed. The search stops at the first match, and
+    /// only the exit code tells whether there was one.
+    pub quiet: bool,
+}
+
+impl Limits {
+    /// The number of matches after which the search stops, if any.
+    pub fn stop_after(&self) -> Option<usize> {
+        if self.quiet {
+            Some(1)
+        } else {
+            self.max_results
+        }
+    }
+}
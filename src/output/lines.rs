@@ -1,29 +1,42 @@
 use std::io::{Write, self};
-use ansi_term::{ANSIString, ANSIStrings};
+use std::ops::Range;
+use ansi_term::{ANSIByteString, ANSIByteStrings};
 
 use crate::fs::file::File;
 use crate::output::file_path::Options as FileStyle;
 use crate::theme::Theme;
 
 pub struct Render<'a> {
-    pub files: Vec<File<'a>>,
+    /// The files to print, with the byte ranges of their names matched by
+    /// the name filter, which are highlighted.
+    pub files: Vec<(File<'a>, Vec<Range<usize>>)>,
     pub file_style: &'a FileStyle,
     pub theme: &'a Theme,
 }
 
 impl<'a> Render<'a> {
-    pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
-        for file in &self.files {
-            let file_path = self.render_file(file);
-            writeln!(w, "{}", ANSIStrings(&file_path))?;
+    pub fn render<W: Write>(self, w: &mut W) -> io::Result<()> {
+        for (file, highlights) in &self.files {
+            let file_path = self.render_file(file, highlights);
+
+            if self.theme.ui.colourful {
+                ANSIByteStrings(&file_path).write_to(w)?;
+                writeln!(w)?;
+            } else {
+                for bit in &file_path {
+                    w.write_all(bit)?;
+                }
+                writeln!(w)?;
+            }
         }
 
         Ok(())
     }
 
-    fn render_file<'f>(&self, file: &File<'f>) -> Vec<ANSIString<'static>> {
+    fn render_file<'f>(&self, file: &File<'f>, highlights: &[Range<usize>]) -> Vec<ANSIByteString<'static>> {
         self.file_style
             .for_file(file, self.theme)
+            .with_highlights(highlights.to_vec())
             .paint()
     }
 }
\ No newline at end of file
@@ -3,6 +3,14 @@ pub use escape::escape;
 
 pub mod file_path;
 
+pub mod hyperlink;
+
+pub mod limits;
+
 pub mod lines;
 
-pub mod render;
\ No newline at end of file
+pub mod quoting;
+
+pub mod render;
+
+pub mod report;
\ No newline at end of file
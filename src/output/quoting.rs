@@ -0,0 +1,255 @@
+use ansi_term::{ANSIByteString, Style};
+
+use crate::output::escape;
+
+/// How the paths are quoted and escaped when they are printed, in the
+/// style of the `--quoting-style` option of GNU `ls`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QuotingStyle {
+    /// The control characters and the invalid UTF-8 are escaped, without
+    /// quotes.
+    #[default]
+    Escape,
+
+    /// The bytes are printed as they are.
+    Literal,
+
+    /// The paths are put between single quotes when the shell would
+    /// interpret some of their characters. The paths with control
+    /// characters are written as `$'...'` strings, so the terminal never
+    /// receives them raw.
+    Shell,
+
+    /// The paths are always put between single quotes, or written as
+    /// `$'...'` strings when they have control characters.
+    ShellAlways,
+
+    /// Like `Shell`, but the paths with control characters or invalid UTF-8
+    /// are written as `$'...'` strings, with the bytes escaped.
+    ShellEscape,
+
+    /// The paths are written as C strings, between double quotes.
+    C,
+}
+
+/// The quoting chosen for a path, from its style and its bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    Escaped,
+    Raw,
+    SingleQuotes,
+    DollarQuotes,
+    DoubleQuotes,
+}
+
+impl QuotingStyle {
+    /// The quoting of the given path. A path is quoted as a whole, even
+    /// when it's painted in several bits.
+    pub fn quoting(self, path: &[u8]) -> Quoting {
+        match self {
+            Self::Escape      => Quoting::Escaped,
+            Self::Literal     => Quoting::Raw,
+            Self::C           => Quoting::DoubleQuotes,
+            Self::ShellEscape if !is_printable(path) => Quoting::DollarQuotes,
+            Self::Shell | Self::ShellAlways if has_control_chars(path) => Quoting::DollarQuotes,
+            Self::ShellAlways => Quoting::SingleQuotes,
+            Self::Shell | Self::ShellEscape => {
+                if path.is_empty() || !path.iter().all(|byte| is_shell_safe(*byte)) {
+                    Quoting::SingleQuotes
+                } else {
+                    Quoting::Raw
+                }
+            },
+        }
+    }
+}
+
+impl Quoting {
+    /// The quote written before the path.
+    pub fn open(self) -> &'static [u8] {
+        match self {
+            Self::Escaped | Self::Raw => b"",
+            Self::SingleQuotes        => b"'",
+            Self::DollarQuotes        => b"$'",
+            Self::DoubleQuotes        => b"\"",
+        }
+    }
+
+    /// The quote written after the path.
+    pub fn close(self) -> &'static [u8] {
+        match self {
+            Self::Escaped | Self::Raw => b"",
+            Self::SingleQuotes | Self::DollarQuotes => b"'",
+            Self::DoubleQuotes        => b"\"",
+        }
+    }
+
+    /// Paint some bytes of a quoted path. The escape sequences are painted
+    /// with the `bad` style, the rest with the `good` one.
+    pub fn paint(self, bytes: &[u8], bits: &mut Vec<ANSIByteString<'_>>, good: Style, bad: Style) {
+        match self {
+            Self::Escaped => escape(bytes, bits, good, bad),
+            Self::Raw     => bits.push(good.paint(bytes.to_vec())),
+            _ => {
+                let mut plain = Vec::new();
+                let mut rest = bytes;
+
+                // Like `escape`, the UTF-8 is validated once per run of
+                // valid bytes, so a path is only read once.
+                while !rest.is_empty() {
+                    let (valid, invalid) = match std::str::from_utf8(rest) {
+                        Ok(string) => (string, &[][..]),
+                        Err(e) => {
+                            let (valid, after) = rest.split_at(e.valid_up_to());
+                            let invalid_len = e.error_len().unwrap_or(after.len());
+
+                            (std::str::from_utf8(valid).unwrap_or_default(), &after[..invalid_len])
+                        },
+                    };
+
+                    for c in valid.chars() {
+                        match self.escape_char(c) {
+                            Some(escaped) => self.push_escaped(escaped, &mut plain, bits, good, bad),
+                            None => plain.extend(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                        }
+                    }
+
+                    for byte in invalid {
+                        match self.escape_invalid(*byte) {
+                            Some(escaped) => self.push_escaped(escaped, &mut plain, bits, good, bad),
+                            None => plain.push(*byte),
+                        }
+                    }
+
+                    rest = &rest[valid.len() + invalid.len()..];
+                }
+
+                if !plain.is_empty() {
+                    bits.push(good.paint(plain));
+                }
+            },
+        }
+    }
+
+    /// Paint an escape sequence, after the plain bytes before it.
+    fn push_escaped(self, escaped: String, plain: &mut Vec<u8>, bits: &mut Vec<ANSIByteString<'_>>, good: Style, bad: Style) {
+        if !plain.is_empty() {
+            bits.push(good.paint(std::mem::take(plain)));
+        }
+        bits.push(bad.paint(escaped.into_bytes()));
+    }
+
+    /// The quoted path, unpainted.
+    pub fn quote(self, path: &[u8]) -> Vec<u8> {
+        let mut bits = Vec::new();
+        self.paint(path, &mut bits, Style::default(), Style::default());
+
+        let mut quoted = self.open().to_vec();
+        quoted.extend(bits.iter().flat_map(|bit| bit.iter()));
+        quoted.extend(self.close());
+        quoted
+    }
+
+    /// How a character is written between the quotes, if it has to be
+    /// escaped.
+    fn escape_char(self, c: char) -> Option<String> {
+        match (self, c) {
+            (Self::SingleQuotes, '\'') => Some(String::from("'\\''")),
+            (Self::SingleQuotes, _)    => None,
+
+            (_, '\\')                  => Some(String::from("\\\\")),
+            (Self::DollarQuotes, '\'') => Some(String::from("\\'")),
+            (Self::DoubleQuotes, '"')  => Some(String::from("\\\"")),
+            (_, '\n')                  => Some(String::from("\\n")),
+            (_, '\t')                  => Some(String::from("\\t")),
+            (_, '\r')                  => Some(String::from("\\r")),
+            (_, c) if c.is_control() => {
+                let mut utf8 = [0; 4];
+                c.encode_utf8(&mut utf8).bytes().map(|byte| self.escape_invalid(byte)).collect()
+            },
+            _ => None,
+        }
+    }
+
+    /// How a byte that isn't valid UTF-8, or a byte of a control character,
+    /// is written between the quotes.
+    fn escape_invalid(self, byte: u8) -> Option<String> {
+        match self {
+            Self::DollarQuotes => Some(format!("\\x{:02x}", byte)),
+            Self::DoubleQuotes => Some(format!("\\{:03o}", byte)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the shell reads the byte as itself outside of quotes.
+fn is_shell_safe(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"_-+=.,:/@%^".contains(&byte) || byte >= 0x80
+}
+
+/// Whether the path has a control character, in its valid UTF-8.
+fn has_control_chars(path: &[u8]) -> bool {
+    String::from_utf8_lossy(path).chars().any(char::is_control)
+}
+
+/// Whether the path is valid UTF-8 without any control character.
+fn is_printable(path: &[u8]) -> bool {
+    std::str::from_utf8(path).map_or(false, |path| !path.chars().any(char::is_control))
+}
+
+#[cfg(test)]
+mod quoting_test {
+    use super::*;
+
+    fn quote(style: QuotingStyle, path: &[u8]) -> Vec<u8> {
+        style.quoting(path).quote(path)
+    }
+
+    #[test]
+    fn shell_only_when_needed() {
+        assert_eq!(quote(QuotingStyle::Shell, b"dir/file.rs"), b"dir/file.rs");
+        assert_eq!(quote(QuotingStyle::Shell, b"a b$c"), b"'a b$c'");
+        assert_eq!(quote(QuotingStyle::Shell, b"it's"), b"'it'\\''s'");
+    }
+
+    #[test]
+    fn shell_always() {
+        assert_eq!(quote(QuotingStyle::ShellAlways, b"file"), b"'file'");
+    }
+
+    #[test]
+    fn shell_control_characters() {
+        assert_eq!(quote(QuotingStyle::Shell, b"esc\x1b[31mred"), b"$'esc\\x1b[31mred'");
+        assert_eq!(quote(QuotingStyle::ShellAlways, b"esc\x1b[31mred"), b"$'esc\\x1b[31mred'");
+        assert_eq!(quote(QuotingStyle::Shell, "csi\u{9b}".as_bytes()), b"$'csi\\xc2\\x9b'");
+        assert_eq!(quote(QuotingStyle::Shell, b"bad\xff name"), b"'bad\xff name'");
+    }
+
+    #[test]
+    fn shell_escape() {
+        assert_eq!(quote(QuotingStyle::ShellEscape, b"a b"), b"'a b'");
+        assert_eq!(quote(QuotingStyle::ShellEscape, b"new\nline\xff'"), b"$'new\\nline\\xff\\''");
+        assert_eq!(quote(QuotingStyle::ShellEscape, "é\x01".as_bytes()), "$'é\\x01'".as_bytes());
+    }
+
+    #[test]
+    fn c() {
+        assert_eq!(quote(QuotingStyle::C, b"say \"hi\"\n\xff"), b"\"say \\\"hi\\\"\\n\\377\"");
+    }
+
+    #[test]
+    fn c1_controls() {
+        assert_eq!(quote(QuotingStyle::ShellEscape, "a\u{85}b".as_bytes()), b"$'a\\xc2\\x85b'");
+        assert_eq!(quote(QuotingStyle::C, "a\u{9b}b".as_bytes()), b"\"a\\302\\233b\"");
+    }
+
+    #[test]
+    fn invalid_between_valid() {
+        assert_eq!(quote(QuotingStyle::C, b"\xffa\xfe\xfdb\xe2\x82"), b"\"\\377a\\376\\375b\\342\\202\"");
+    }
+
+    #[test]
+    fn literal() {
+        assert_eq!(quote(QuotingStyle::Literal, b"a\nb"), b"a\nb");
+    }
+}
@@ -2,6 +2,7 @@ use ansi_term::{ANSIString, Style};
 
 use crate::fs::fields::Type;
 
+#[allow(dead_code)]
 impl Type {
     pub fn render<C: Colours>(self, colours: &C) -> ANSIString<'static> {
         match self {
@@ -17,6 +18,7 @@ impl Type {
     }
 }
 
+#[allow(dead_code)]
 pub trait Colours {
     fn normal(&self) -> Style;
     fn directory(&self) -> Style;
@@ -0,0 +1,94 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+/// How the errors met during the search are reported.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    /// Whether the paths that can't be read for lack of permission are
+    /// skipped silently, without changing the exit code.
+    pub skip_denied: bool,
+
+    /// Whether the errors are not printed. They still change the exit code.
+    pub quiet_errors: bool,
+
+    /// The format the errors are printed in.
+    pub format: ErrorFormat,
+}
+
+/// The format of the errors printed to the standard error.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// `search: path: message`, for humans.
+    #[default]
+    Text,
+
+    /// One JSON object per line, with the `path`, the `message` and the
+    /// OS error `code` if there is one, for tools.
+    Json,
+}
+
+impl Report {
+    /// Whether the given error is neither printed nor counted.
+    pub fn is_ignored(&self, error: &io::Error) -> bool {
+        self.skip_denied && error.kind() == io::ErrorKind::PermissionDenied
+    }
+
+    /// Write an error met on a path, in the format chosen by the user.
+    pub fn write<W: Write>(&self, w: &mut W, path: &Path, error: &io::Error) -> io::Result<()> {
+        if self.quiet_errors {
+            return Ok(());
+        }
+
+        match self.format {
+            ErrorFormat::Text => writeln!(w, "search: {}: {}", path.display(), error),
+            ErrorFormat::Json => {
+                let code = error.raw_os_error().map_or_else(|| String::from("null"), |code| code.to_string());
+
+                writeln!(w, "{{\"path\":{},\"message\":{},\"code\":{}}}",
+                    json_string(&path.to_string_lossy()), json_string(&error.to_string()), code)
+            },
+        }
+    }
+}
+
+/// Quote and escape a string as a JSON string.
+fn json_string(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() + 2);
+    output.push('"');
+
+    for c in input.chars() {
+        match c {
+            '"'  => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => output.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod report_test {
+    use super::*;
+
+    #[test]
+    fn json_escapes() {
+        assert_eq!(json_string("a\"b\\c\n\u{1b}"), "\"a\\\"b\\\\c\\n\\u001b\"");
+    }
+
+    #[test]
+    fn json_line() {
+        let report = Report { format: ErrorFormat::Json, ..Report::default() };
+        let mut output = Vec::new();
+        report.write(&mut output, Path::new("dir/x"), &io::Error::from_raw_os_error(13)).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("{\"path\":\"dir/x\",\"message\":\"Permission denied"));
+        assert!(output.ends_with(",\"code\":13}\n"));
+    }
+}
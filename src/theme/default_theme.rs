@@ -8,6 +8,8 @@ use crate::theme::NoFileColours;
 impl UiStyles {
     pub fn default_theme() -> Self {
         Self {
+            colourful: true,
+
             filekinds: FileKinds {
                 normal:       Style::default(),
                 directory:    Blue.bold(),
@@ -19,15 +21,32 @@ impl UiStyles {
                 special:      Yellow.normal(),
                 executable:   Green.bold(),
             },
+
+            parent_path:         Style::default(),
+            symlink_path:        Cyan.normal(),
+            normal_arrow:        Style::default(),
+            broken_symlink:      Red.normal(),
+            broken_filename:     Red.underline(),
+            control_char:        Red.normal(),
+            broken_control_char: Red.bold(),
+            match_highlight:     Yellow.bold(),
         }
     }
 }
 
 impl Theme {
     pub fn default_theme() -> Self {
+        let ui = UiStyles::default_theme();
+        let exts = Box::new(NoFileColours);
+
+        Theme { ui, exts }
+    }
+
+    /// A theme without any colour.
+    pub fn plain() -> Self {
         let ui = UiStyles::default();
         let exts = Box::new(NoFileColours);
 
         Theme { ui, exts }
     }
-}
\ No newline at end of file
+}
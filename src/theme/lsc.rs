@@ -0,0 +1,192 @@
+use std::iter::Peekable;
+
+use ansi_term::{Colour, Style};
+use ansi_term::Colour::*;
+
+/// A colour definition string in the `LS_COLORS` format: a list of
+/// `key=value` pairs separated by colons, where each value is a list of
+/// ANSI SGR codes separated by semicolons.
+///
+///   di=01;34:ln=36:*.rs=38;5;208
+pub struct LSColors<'var>(pub &'var str);
+
+impl<'var> LSColors<'var> {
+    /// Call the callback for each well-formed pair of the string,
+    /// ignoring the empty and invalid ones.
+    pub fn each_pair<C>(&mut self, mut callback: C)
+    where C: FnMut(Pair<'var>)
+    {
+        for next in self.0.split(':') {
+            let bits = next.split('=').collect::<Vec<_>>();
+
+            if bits.len() == 2 && !bits[0].is_empty() && !bits[1].is_empty() {
+                callback(Pair { key: bits[0], value: bits[1] });
+            }
+        }
+    }
+}
+
+/// A single `key=value` pair of a colour definition string.
+#[derive(PartialEq, Debug)]
+pub struct Pair<'var> {
+    pub key: &'var str,
+    pub value: &'var str,
+}
+
+impl<'var> Pair<'var> {
+    /// Parse the value of the pair into a style. Unknown codes are ignored.
+    pub fn to_style(&self) -> Style {
+        let mut style = Style::default();
+        let mut codes = self.value.split(';').peekable();
+
+        while let Some(code) = codes.next() {
+            match code {
+                "0" | "00" => style = Style::default(),
+                "1" | "01" => style = style.bold(),
+                "2" | "02" => style = style.dimmed(),
+                "3" | "03" => style = style.italic(),
+                "4" | "04" => style = style.underline(),
+                "5" | "05" => style = style.blink(),
+                "7" | "07" => style = style.reverse(),
+                "8" | "08" => style = style.hidden(),
+                "9" | "09" => style = style.strikethrough(),
+
+                "38" => if let Some(colour) = parse_extended(&mut codes) { style = style.fg(colour) },
+                "48" => if let Some(colour) = parse_extended(&mut codes) { style = style.on(colour) },
+                "39" => style.foreground = None,
+                "49" => style.background = None,
+
+                code => {
+                    if let Some(colour) = parse_colour(code, 30) {
+                        style = style.fg(colour);
+                    } else if let Some(colour) = parse_colour(code, 40) {
+                        style = style.on(colour);
+                    } else if let Some(colour) = parse_colour(code, 90) {
+                        style = style.fg(brighten(colour));
+                    } else if let Some(colour) = parse_colour(code, 100) {
+                        style = style.on(brighten(colour));
+                    }
+                },
+            }
+        }
+
+        style
+    }
+}
+
+/// Parse one of the eight basic colours, numbered from `base`.
+fn parse_colour(code: &str, base: u8) -> Option<Colour> {
+    let number = code.parse::<u8>().ok()?.checked_sub(base)?;
+
+    match number {
+        0 => Some(Black),
+        1 => Some(Red),
+        2 => Some(Green),
+        3 => Some(Yellow),
+        4 => Some(Blue),
+        5 => Some(Purple),
+        6 => Some(Cyan),
+        7 => Some(White),
+        _ => None,
+    }
+}
+
+/// The bright variant of a basic colour, in the 256-colour palette.
+fn brighten(colour: Colour) -> Colour {
+    match colour {
+        Black  => Fixed(8),
+        Red    => Fixed(9),
+        Green  => Fixed(10),
+        Yellow => Fixed(11),
+        Blue   => Fixed(12),
+        Purple => Fixed(13),
+        Cyan   => Fixed(14),
+        White  => Fixed(15),
+        other  => other,
+    }
+}
+
+/// Parse the arguments of an extended colour code: `5;n` for the
+/// 256-colour palette and `2;r;g;b` for a true colour.
+fn parse_extended<'a, I>(codes: &mut Peekable<I>) -> Option<Colour>
+where I: Iterator<Item = &'a str>
+{
+    match codes.next()? {
+        "5" => {
+            let n = codes.next()?.parse().ok()?;
+            Some(Fixed(n))
+        },
+        "2" => {
+            let r = codes.next()?.parse().ok()?;
+            let g = codes.next()?.parse().ok()?;
+            let b = codes.next()?.parse().ok()?;
+            Some(RGB(r, g, b))
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod lsc_test {
+    use super::*;
+
+    fn pairs(input: &str) -> Vec<(&str, &str)> {
+        let mut pairs = Vec::new();
+        LSColors(input).each_pair(|pair| pairs.push((pair.key, pair.value)));
+        pairs
+    }
+
+    fn style(value: &str) -> Style {
+        Pair { key: "di", value }.to_style()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(pairs(""), vec![]);
+    }
+
+    #[test]
+    fn several_pairs() {
+        assert_eq!(pairs("di=34:ln=36"), vec![("di", "34"), ("ln", "36")]);
+    }
+
+    #[test]
+    fn invalid_pairs_are_skipped() {
+        assert_eq!(pairs("di=:=34::ln=36=1:*.rs=31"), vec![("*.rs", "31")]);
+    }
+
+    #[test]
+    fn bold_foreground() {
+        assert_eq!(style("01;34"), Blue.bold());
+    }
+
+    #[test]
+    fn foreground_and_background() {
+        assert_eq!(style("31;42"), Red.on(Green));
+    }
+
+    #[test]
+    fn bright_foreground() {
+        assert_eq!(style("91"), Fixed(9).normal());
+    }
+
+    #[test]
+    fn fixed_colour() {
+        assert_eq!(style("38;5;208"), Fixed(208).normal());
+    }
+
+    #[test]
+    fn true_colour() {
+        assert_eq!(style("48;2;1;2;3;4"), Style::default().on(RGB(1, 2, 3)).underline());
+    }
+
+    #[test]
+    fn reset() {
+        assert_eq!(style("1;31;0;32"), Green.normal());
+    }
+
+    #[test]
+    fn truncated_extended_colour() {
+        assert_eq!(style("38;5"), Style::default());
+    }
+}
@@ -3,11 +3,122 @@ use ansi_term::Style;
 use crate::fs::file::File;
 use crate::output::file_path::Colours as FileNameColours;
 use crate::output::render;
+use crate::theme::lsc::LSColors;
 use crate::theme::ui_styles::UiStyles;
 
 mod default_theme;
+mod lsc;
+mod palette;
+mod theme_file;
 mod ui_styles;
 
+pub use crate::theme::palette::ColourSupport;
+pub use crate::theme::theme_file::ThemeFile;
+
+/// The theme options given by the user.
+#[derive(PartialEq, Debug)]
+pub struct Options {
+    /// Whether the output is coloured.
+    pub use_colours: UseColours,
+
+    /// The colours the terminal can display.
+    pub colour_support: ColourSupport,
+
+    /// The theme chosen with `--theme`, used instead of the default one.
+    pub file: Option<ThemeFile>,
+
+    /// The colour definitions read from the environment.
+    pub definitions: Definitions,
+}
+
+/// The colour definitions of the `LS_COLORS` and `SEARCH_COLORS`
+/// environment variables. The latter is applied last, so it overrides
+/// the former.
+#[derive(PartialEq, Debug, Default)]
+pub struct Definitions {
+    pub ls: Option<String>,
+    pub search: Option<String>,
+}
+
+/// Under what circumstances we should output coloured text.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub enum UseColours {
+    /// Display them even when output isn’t going to a terminal.
+    Always,
+
+    /// Display them when output is going to a terminal, but not otherwise.
+    #[default]
+    Automatic,
+
+    /// Never display them, even when output is going to a terminal.
+    Never,
+}
+
+impl Options {
+    /// Build the theme from the default one. A theme file chosen with
+    /// `--theme` is applied over `LS_COLORS`, which distributions often set
+    /// for every user, and `SEARCH_COLORS` over both. Colours the terminal
+    /// can’t display are replaced by the closest ones it can.
+    pub fn to_theme(&self, isatty: bool) -> Theme {
+        if self.use_colours == UseColours::Never || (self.use_colours == UseColours::Automatic && !isatty) {
+            return Theme::plain();
+        }
+
+        let mut theme = Theme::default_theme();
+        let mut exts = ExtensionMappings::default();
+
+        self.definitions.parse_ls_into(&mut theme.ui, &mut exts);
+
+        if let Some(file) = &self.file {
+            theme.ui = file.ui.clone();
+
+            for (pattern, style) in &file.extensions {
+                exts.add(pattern, *style);
+            }
+        }
+
+        self.definitions.parse_search_into(&mut theme.ui, &mut exts);
+
+        theme.ui.map_styles(|style| self.colour_support.downgrade(style));
+        for mapping in &mut exts.mappings {
+            mapping.1 = self.colour_support.downgrade(mapping.1);
+        }
+
+        if !exts.mappings.is_empty() {
+            theme.exts = Box::new(exts);
+        }
+
+        theme
+    }
+}
+
+impl Definitions {
+    /// Apply the `LS_COLORS` definitions to the given styles. The keys
+    /// starting with `*` are glob patterns matched against file names, and
+    /// the other keys that aren’t known, such as `tw` or `su`, are ignored.
+    fn parse_ls_into(&self, ui: &mut UiStyles, exts: &mut ExtensionMappings) {
+        if let Some(ls) = &self.ls {
+            LSColors(ls).each_pair(|pair| {
+                if !ui.set_ls(&pair) && pair.key.starts_with('*') {
+                    exts.add(pair.key, pair.to_style());
+                }
+            });
+        }
+    }
+
+    /// Apply the `SEARCH_COLORS` definitions to the given styles, like the
+    /// `LS_COLORS` ones.
+    fn parse_search_into(&self, ui: &mut UiStyles, exts: &mut ExtensionMappings) {
+        if let Some(search) = &self.search {
+            LSColors(search).each_pair(|pair| {
+                if !ui.set_search(&pair) && pair.key.starts_with('*') {
+                    exts.add(pair.key, pair.to_style());
+                }
+            });
+        }
+    }
+}
+
 pub struct Theme {
     pub ui: UiStyles,
     pub exts: Box<dyn FileColours>
@@ -22,12 +133,14 @@ impl FileNameColours for Theme {
         self.exts.colour_file(file).unwrap_or(self.ui.filekinds.normal)
     }
 
-    fn symlink_path(&self) -> Style { self.ui.filekinds.normal }
-    fn normal_arrow(&self) -> Style { self.ui.filekinds.normal }
-    fn broken_symlink(&self) -> Style { self.ui.filekinds.normal }
-    fn broken_filename(&self) -> Style { self.ui.filekinds.normal }
-    fn control_char(&self) -> Style { self.ui.filekinds.normal }
-    fn broken_control_char(&self) -> Style { self.ui.filekinds.normal }
+    fn parent_path(&self) -> Style { self.ui.parent_path }
+    fn symlink_path(&self) -> Style { self.ui.symlink_path }
+    fn normal_arrow(&self) -> Style { self.ui.normal_arrow }
+    fn broken_symlink(&self) -> Style { self.ui.broken_symlink }
+    fn broken_filename(&self) -> Style { self.ui.broken_filename }
+    fn control_char(&self) -> Style { self.ui.control_char }
+    fn broken_control_char(&self) -> Style { self.ui.broken_control_char }
+    fn match_highlight(&self) -> Style { self.ui.match_highlight }
     fn executable_file(&self) -> Style { self.ui.filekinds.executable }
 }
 
@@ -56,12 +169,78 @@ struct ExtensionMappings {
     mappings: Vec<(glob::Pattern, Style)>,
 }
 
+impl ExtensionMappings {
+    /// Add a mapping for the given glob pattern, ignoring invalid ones.
+    fn add(&mut self, pattern: &str, style: Style) {
+        if let Ok(pattern) = glob::Pattern::new(pattern) {
+            self.mappings.push((pattern, style));
+        }
+    }
+}
+
 // Loop through backwards so that colours specified later in the list override
 // colours specified earlier, like we do with options and strict mode
 impl FileColours for ExtensionMappings {
     fn colour_file(&self, file: &File<'_>) -> Option<Style> {
         self.mappings.iter().rev()
-            .find(|t| t.0.matches(&file.name))
+            .find(|t| t.0.matches(&file.name.to_string_lossy()))
             .map (|t| t.1)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod theme_test {
+    use super::*;
+    use ansi_term::Colour::*;
+
+    fn mappings(ls: &str) -> Vec<(String, Style)> {
+        let definitions = Definitions { ls: Some(ls.to_string()), search: None };
+        let mut ui = UiStyles::default_theme();
+        let mut exts = ExtensionMappings::default();
+        definitions.parse_ls_into(&mut ui, &mut exts);
+
+        exts.mappings.into_iter().map(|(pattern, style)| (pattern.as_str().to_string(), style)).collect()
+    }
+
+    #[test]
+    fn globs() {
+        assert_eq!(mappings("*.rs=31:*README=32"), vec![(String::from("*.rs"), Red.normal()), (String::from("*README"), Green.normal())]);
+    }
+
+    #[test]
+    fn unknown_keys_are_ignored() {
+        assert_eq!(mappings("no=00:tw=30;42:ow=34;42:st=37;44:su=37;41:sg=30;43:ca=30;41:do=01;35:rs=0:mh=00"), vec![]);
+    }
+
+    fn options(file: Option<ThemeFile>, ls: &str, search: Option<&str>) -> Options {
+        Options {
+            use_colours: UseColours::Always,
+            colour_support: ColourSupport::TrueColour,
+            file,
+            definitions: Definitions { ls: Some(ls.to_string()), search: search.map(str::to_string) },
+        }
+    }
+
+    #[test]
+    fn theme_file_wins_over_ls_colors() {
+        let file = ThemeFile::load("high-contrast", None).unwrap();
+        let directory = file.ui.filekinds.directory;
+
+        let theme = options(Some(file), "di=01;34:*.rs=31", None).to_theme(true);
+        assert_eq!(theme.ui.filekinds.directory, directory);
+        assert_ne!(directory, Blue.bold());
+    }
+
+    #[test]
+    fn ls_colors_without_theme_file() {
+        let theme = options(None, "di=01;34", None).to_theme(true);
+        assert_eq!(theme.ui.filekinds.directory, Blue.bold());
+    }
+
+    #[test]
+    fn search_colors_win_over_theme_file() {
+        let file = ThemeFile::load("high-contrast", None).unwrap();
+        let theme = options(Some(file), "di=01;34", Some("di=32")).to_theme(true);
+        assert_eq!(theme.ui.filekinds.directory, Green.normal());
+    }
+}
@@ -0,0 +1,152 @@
+use ansi_term::{Colour, Style};
+use ansi_term::Colour::*;
+
+/// The colours a terminal is able to display.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum ColourSupport {
+    /// The eight basic colours, and their bright variants.
+    Ansi16,
+
+    /// The 256-colour palette.
+    Ansi256,
+
+    /// Any 24-bit colour.
+    TrueColour,
+}
+
+/// The RGB values of the 16 first colours of the palette, as xterm
+/// displays them.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),       (205, 0, 0),     (0, 205, 0),     (205, 205, 0),
+    (0, 0, 238),     (205, 0, 205),   (0, 205, 205),   (229, 229, 229),
+    (127, 127, 127), (255, 0, 0),     (0, 255, 0),     (255, 255, 0),
+    (92, 92, 255),   (255, 0, 255),   (0, 255, 255),   (255, 255, 255),
+];
+
+/// The levels of each channel of the 6×6×6 colour cube of the palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColourSupport {
+    /// Replace the colours of a style the terminal can’t display by the
+    /// closest ones it can.
+    pub fn downgrade(self, style: Style) -> Style {
+        Style {
+            foreground: style.foreground.map(|c| self.downgrade_colour(c)),
+            background: style.background.map(|c| self.downgrade_colour(c)),
+            ..style
+        }
+    }
+
+    fn downgrade_colour(self, colour: Colour) -> Colour {
+        match (self, colour) {
+            (Self::TrueColour, colour) => colour,
+            (Self::Ansi256, RGB(r, g, b)) => Fixed(nearest_fixed(r, g, b)),
+            (Self::Ansi256, colour) => colour,
+            (Self::Ansi16, Fixed(n)) => basic(nearest_ansi(fixed_to_rgb(n))),
+            (Self::Ansi16, RGB(r, g, b)) => basic(nearest_ansi((r, g, b))),
+            (Self::Ansi16, colour) => colour,
+        }
+    }
+}
+
+/// The basic colour of an index of the 16 first colours of the palette. The
+/// bright variants lose their brightness, as they can only be written with
+/// the 256-colour codes.
+fn basic(index: u8) -> Colour {
+    match index % 8 {
+        0 => Black,
+        1 => Red,
+        2 => Green,
+        3 => Yellow,
+        4 => Blue,
+        5 => Purple,
+        6 => Cyan,
+        _ => White,
+    }
+}
+
+/// The RGB value of a colour of the palette.
+fn fixed_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => ANSI_RGB[usize::from(n)],
+        16..=231 => {
+            let n = n - 16;
+            let level = |i: u8| CUBE_LEVELS[usize::from(i)];
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        },
+        _ => {
+            let grey = 8 + (n - 232) * 10;
+            (grey, grey, grey)
+        },
+    }
+}
+
+/// The index of the closest colour among the 16 first colours of the palette.
+fn nearest_ansi(rgb: (u8, u8, u8)) -> u8 {
+    (0..16u8)
+        .min_by_key(|i| distance(rgb, ANSI_RGB[usize::from(*i)]))
+        .unwrap_or(0)
+}
+
+/// The index of the closest colour of the colour cube or of the grey ramp
+/// of the palette.
+fn nearest_fixed(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| {
+        (0..6u8)
+            .min_by_key(|i| (i32::from(CUBE_LEVELS[usize::from(*i)]) - i32::from(c)).abs())
+            .unwrap_or(0)
+    };
+
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+    let average = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance((r, g, b), fixed_to_rgb(grey)) < distance((r, g, b), fixed_to_rgb(cube)) {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).unsigned_abs().pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod palette_test {
+    use super::*;
+
+    #[test]
+    fn true_colour_is_untouched() {
+        let style = RGB(1, 2, 3).on(Fixed(200));
+        assert_eq!(ColourSupport::TrueColour.downgrade(style), style);
+    }
+
+    #[test]
+    fn rgb_to_cube() {
+        assert_eq!(ColourSupport::Ansi256.downgrade(RGB(255, 135, 0).bold()), Fixed(208).bold());
+    }
+
+    #[test]
+    fn rgb_to_grey() {
+        assert_eq!(ColourSupport::Ansi256.downgrade(RGB(48, 48, 48).normal()), Fixed(236).normal());
+    }
+
+    #[test]
+    fn fixed_to_basic() {
+        assert_eq!(ColourSupport::Ansi16.downgrade(Fixed(196).normal()), Red.normal());
+        assert_eq!(ColourSupport::Ansi16.downgrade(Fixed(12).normal()), Blue.normal());
+    }
+
+    #[test]
+    fn rgb_to_basic() {
+        assert_eq!(ColourSupport::Ansi16.downgrade(Style::default().on(RGB(0, 190, 10))), Style::default().on(Green));
+    }
+
+    #[test]
+    fn basic_is_untouched() {
+        assert_eq!(ColourSupport::Ansi16.downgrade(Cyan.on(Black)), Cyan.on(Black));
+    }
+}
@@ -0,0 +1,239 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ansi_term::{Colour, Style};
+use ansi_term::Colour::*;
+
+use crate::theme::lsc::Pair;
+use crate::theme::ui_styles::UiStyles;
+
+/// The themes shipped with search, by name.
+static PRESETS: &[(&str, &str)] = &[
+    ("light",         include_str!("presets/light.toml")),
+    ("dark",          include_str!("presets/dark.toml")),
+    ("high-contrast", include_str!("presets/high-contrast.toml")),
+];
+
+/// A theme loaded from a TOML file.
+///
+/// A theme file has up to three tables: `filekinds` and `ui`, whose keys
+/// are the names of the `UiStyles` fields, and `extensions`, whose keys are
+/// glob patterns matched against file names. The keys a file doesn’t
+/// define keep the style of the default theme.
+///
+///   [filekinds]
+///   directory = "bold blue"
+///
+///   [extensions]
+///   "*.rs" = "38;5;208"
+#[derive(PartialEq, Debug, Clone)]
+pub struct ThemeFile {
+    pub ui: UiStyles,
+    pub extensions: Vec<(String, Style)>,
+}
+
+impl ThemeFile {
+    /// Load the theme with the given name. A name containing a path
+    /// separator or ending with `.toml` is read as a path, otherwise the
+    /// `themes` directory of the configuration directory is searched
+    /// before the presets.
+    pub fn load(name: &str, config_dir: Option<&Path>) -> Result<Self, String> {
+        if name == "default" {
+            return Ok(Self { ui: UiStyles::default_theme(), extensions: Vec::new() });
+        }
+
+        if name.contains(std::path::MAIN_SEPARATOR) || name.ends_with(".toml") {
+            return Self::read(Path::new(name));
+        }
+
+        if let Some(config_dir) = config_dir {
+            let path = themes_dir(config_dir).join(format!("{}.toml", name));
+
+            if path.exists() {
+                return Self::read(&path);
+            }
+        }
+
+        match PRESETS.iter().find(|preset| preset.0 == name) {
+            Some((_, contents)) => Self::parse(contents),
+            None => Err(format!("no theme named {} (presets: default, {})", name, preset_names().join(", "))),
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        Self::parse(&contents)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse the contents of a theme file.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let table = contents.parse::<toml::Table>()
+            .map_err(|e| e.message().to_string())?;
+
+        let mut ui = UiStyles::default_theme();
+        let mut extensions = Vec::new();
+
+        for (section, values) in &table {
+            let values = values.as_table()
+                .ok_or_else(|| format!("[{}] must be a table", section))?;
+
+            for (key, value) in values {
+                let value = value.as_str()
+                    .ok_or_else(|| format!("{}.{} must be a string", section, key))?;
+                let style = parse_style(value)
+                    .map_err(|e| format!("{}.{}: {}", section, key, e))?;
+
+                let known = match section.as_str() {
+                    "filekinds"  => ui.set_filekind(key, style),
+                    "ui"         => ui.set_ui(key, style),
+                    "extensions" => { extensions.push((key.clone(), style)); true },
+                    _ => return Err(format!("unknown table [{}]", section)),
+                };
+
+                if !known {
+                    return Err(format!("unknown key {}.{}", section, key));
+                }
+            }
+        }
+
+        Ok(Self { ui, extensions })
+    }
+}
+
+/// The names of the presets shipped with search.
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|preset| preset.0).collect()
+}
+
+/// The directory the theme files are looked up in.
+pub fn themes_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join("themes")
+}
+
+/// Parse a style, written either as `LS_COLORS` codes (`01;34`) or as a
+/// list of words (`bold blue on #303030`). In the latter form a colour is a
+/// name, a `bright-` name or a `#rrggbb` hex code, and the colour following
+/// `on` is the background.
+fn parse_style(value: &str) -> Result<Style, String> {
+    if value.chars().all(|c| c.is_ascii_digit() || c == ';') {
+        return Ok(Pair { key: "", value }.to_style());
+    }
+
+    let mut style = Style::default();
+    let mut words = value.split_whitespace();
+
+    while let Some(word) = words.next() {
+        style = match word {
+            "bold"          => style.bold(),
+            "dimmed"        => style.dimmed(),
+            "italic"        => style.italic(),
+            "underline"     => style.underline(),
+            "blink"         => style.blink(),
+            "reverse"       => style.reverse(),
+            "hidden"        => style.hidden(),
+            "strikethrough" => style.strikethrough(),
+            "on" => {
+                let colour = words.next().ok_or("missing colour after on")?;
+                style.on(parse_colour(colour)?)
+            },
+            colour => style.fg(parse_colour(colour)?),
+        };
+    }
+
+    Ok(style)
+}
+
+fn parse_colour(word: &str) -> Result<Colour, String> {
+    if let Some(hex) = word.strip_prefix('#') {
+        let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(RGB(r, g, b)),
+            _ => Err(format!("invalid colour {}", word)),
+        };
+    }
+
+    let (bright, name) = match word.strip_prefix("bright-") {
+        Some(name) => (true, name),
+        None => (false, word),
+    };
+
+    let index = match name {
+        "black"              => 0,
+        "red"                => 1,
+        "green"              => 2,
+        "yellow"             => 3,
+        "blue"               => 4,
+        "purple" | "magenta" => 5,
+        "cyan"               => 6,
+        "white"              => 7,
+        _ => return Err(format!("invalid colour {}", word)),
+    };
+
+    Ok(match (bright, index) {
+        (true, n)  => Fixed(8 + n),
+        (false, 0) => Black,
+        (false, 1) => Red,
+        (false, 2) => Green,
+        (false, 3) => Yellow,
+        (false, 4) => Blue,
+        (false, 5) => Purple,
+        (false, 6) => Cyan,
+        _          => White,
+    })
+}
+
+#[cfg(test)]
+mod theme_file_test {
+    use super::*;
+
+    #[test]
+    fn presets_parse() {
+        for name in preset_names() {
+            assert!(ThemeFile::load(name, None).is_ok(), "preset {} doesn’t parse", name);
+        }
+    }
+
+    #[test]
+    fn missing_keys_keep_default() {
+        let theme = ThemeFile::parse("[filekinds]\ndirectory = \"red\"").unwrap();
+        assert_eq!(theme.ui.filekinds.directory, Red.normal());
+        assert_eq!(theme.ui.filekinds.executable, UiStyles::default_theme().filekinds.executable);
+    }
+
+    #[test]
+    fn extensions() {
+        let theme = ThemeFile::parse("[extensions]\n\"*.rs\" = \"31\"").unwrap();
+        assert_eq!(theme.extensions, vec![(String::from("*.rs"), Red.normal())]);
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert!(ThemeFile::parse("[ui]\nnope = \"red\"").is_err());
+    }
+
+    #[test]
+    fn unknown_theme() {
+        assert!(ThemeFile::load("nope", None).is_err());
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(parse_style("bold bright-blue on #102030"), Ok(Fixed(12).on(RGB(0x10, 0x20, 0x30)).bold()));
+    }
+
+    #[test]
+    fn codes() {
+        assert_eq!(parse_style("01;34"), Ok(Blue.bold()));
+    }
+
+    #[test]
+    fn invalid_colour() {
+        assert!(parse_style("bold mauve").is_err());
+        assert!(parse_style("#12345").is_err());
+        assert!(parse_style("on").is_err());
+    }
+}
@@ -1,8 +1,39 @@
 use ansi_term::Style;
 
-#[derive(Debug, Default, PartialEq)]
+use crate::theme::lsc::Pair;
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct UiStyles {
+    /// Whether any style is painted. A theme without colours is rendered
+    /// as plain text.
+    pub colourful: bool,
+
     pub filekinds:  FileKinds,
+
+    /// The style of the directories leading to a file name.
+    pub parent_path: Style,
+
+    /// The style of the path of a symlink’s target, up to its file name.
+    pub symlink_path: Style,
+
+    /// The style of the arrow between a link and its target.
+    pub normal_arrow: Style,
+
+    /// The style of a broken symlink.
+    pub broken_symlink: Style,
+
+    /// The style of the missing target of a broken symlink.
+    pub broken_filename: Style,
+
+    /// The style of a non-displayable control character in a file name.
+    pub control_char: Style,
+
+    /// The style of a control character in the missing target of a broken
+    /// symlink.
+    pub broken_control_char: Style,
+
+    /// The style of the part of a file name matched by the name filter.
+    pub match_highlight: Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -16,4 +47,99 @@ pub struct FileKinds {
     pub socket: Style,
     pub special: Style,
     pub executable: Style,
-}
\ No newline at end of file
+}
+
+impl UiStyles {
+    /// Replace every style by the result of the function.
+    pub fn map_styles<F: Fn(Style) -> Style>(&mut self, f: F) {
+        let kinds = &mut self.filekinds;
+        for style in [
+            &mut kinds.normal, &mut kinds.directory, &mut kinds.symlink, &mut kinds.pipe,
+            &mut kinds.block_device, &mut kinds.char_device, &mut kinds.socket,
+            &mut kinds.special, &mut kinds.executable,
+            &mut self.parent_path, &mut self.symlink_path, &mut self.normal_arrow,
+            &mut self.broken_symlink, &mut self.broken_filename, &mut self.control_char,
+            &mut self.broken_control_char, &mut self.match_highlight,
+        ] {
+            *style = f(*style);
+        }
+    }
+
+    /// Sets a value on this set of colours using one of the keys understood
+    /// by the `LS_COLORS` environment variable. Invalid keys set nothing, but
+    /// return false.
+    pub fn set_ls(&mut self, pair: &Pair<'_>) -> bool {
+        match pair.key {
+            // `ln=target` colours links like their target, which we don’t do.
+            "ln" if pair.value == "target" => {},
+
+            "fi" => self.filekinds.normal       = pair.to_style(),
+            "di" => self.filekinds.directory    = pair.to_style(),
+            "ex" => self.filekinds.executable   = pair.to_style(),
+            "pi" => self.filekinds.pipe         = pair.to_style(),
+            "so" => self.filekinds.socket       = pair.to_style(),
+            "bd" => self.filekinds.block_device = pair.to_style(),
+            "cd" => self.filekinds.char_device  = pair.to_style(),
+            "ln" => self.filekinds.symlink      = pair.to_style(),
+            "or" => self.broken_symlink         = pair.to_style(),
+            "mi" => self.broken_filename        = pair.to_style(),
+             _   => return false,
+        }
+
+        true
+    }
+
+    /// Sets a value on this set of colours using one of the keys understood
+    /// by the `SEARCH_COLORS` environment variable, which also accepts every
+    /// `LS_COLORS` key. Invalid keys set nothing, but return false.
+    pub fn set_search(&mut self, pair: &Pair<'_>) -> bool {
+        match pair.key {
+            "pp" => self.parent_path     = pair.to_style(),
+            "lp" => self.symlink_path    = pair.to_style(),
+            "xa" => self.normal_arrow    = pair.to_style(),
+            "cc" => self.control_char    = pair.to_style(),
+            "bc" => self.broken_control_char = pair.to_style(),
+            "mh" => self.match_highlight = pair.to_style(),
+             _   => return self.set_ls(pair),
+        }
+
+        true
+    }
+
+    /// Sets a value of the `filekinds` table of a theme file. Invalid keys
+    /// set nothing, but return false.
+    pub fn set_filekind(&mut self, key: &str, style: Style) -> bool {
+        match key {
+            "normal"       => self.filekinds.normal       = style,
+            "directory"    => self.filekinds.directory    = style,
+            "symlink"      => self.filekinds.symlink      = style,
+            "pipe"         => self.filekinds.pipe         = style,
+            "block_device" => self.filekinds.block_device = style,
+            "char_device"  => self.filekinds.char_device  = style,
+            "socket"       => self.filekinds.socket       = style,
+            "special"      => self.filekinds.special      = style,
+            "executable"   => self.filekinds.executable   = style,
+             _             => return false,
+        }
+
+        true
+    }
+
+    /// Sets a value of the `ui` table of a theme file. Invalid keys set
+    /// nothing, but return false.
+    pub fn set_ui(&mut self, key: &str, style: Style) -> bool {
+        match key {
+            "parent_path"         => self.parent_path         = style,
+            "symlink_path"        => self.symlink_path        = style,
+            "normal_arrow"        => self.normal_arrow        = style,
+            "broken_symlink"      => self.broken_symlink      = style,
+            "broken_filename"     => self.broken_filename     = style,
+            "control_char"        => self.control_char        = style,
+            "broken_control_char" => self.broken_control_char = style,
+            "match_highlight"     => self.match_highlight     = style,
+             _                    => return false,
+        }
+
+        true
+    }
+}